@@ -2,14 +2,34 @@
 
 /// Syscall
 pub mod syscall {
+	pub const READ: usize = 63;
 	pub const WRITE: usize = 64;
 	pub const EXIT: usize = 93;
 	pub const NANOSLEEP: usize = 101;
 	pub const YIELD: usize = 124;
+	pub const KILL: usize = 129;
+	pub const RT_SIGACTION: usize = 134;
+	pub const RT_SIGPROCMASK: usize = 135;
+	pub const RT_SIGRETURN: usize = 139;
 	pub const GETTIMEOFDAY: usize = 169;
+	pub const GETPID: usize = 172;
+	pub const GETPPID: usize = 173;
+	pub const GETTID: usize = 178;
+	pub const BRK: usize = 214;
+	pub const MUNMAP: usize = 215;
+	pub const FORK: usize = 220;
+	pub const EXECVE: usize = 221;
+	pub const MMAP: usize = 222;
+	pub const MPROTECT: usize = 226;
+	pub const WAIT4: usize = 260;
+	pub const SPAWN: usize = 400;
+	pub const TASK_INFO: usize = 410;
 	pub const SETPRIORITY: usize = 140;
+	pub const TIMES: usize = 153;
+	pub const GETRUSAGE: usize = 165;
 
 	#[repr(C)]
+	#[derive(Copy, Clone)]
 	pub struct KernelTimespec {
 		pub tv_sec:  i64,
 		pub tv_nsec: i64,
@@ -24,7 +44,7 @@ pub mod syscall {
 	}
 
 	#[repr(C)]
-	#[derive(Debug, Default)]
+	#[derive(Copy, Clone, Debug, Default)]
 	pub struct TimeVal {
 		pub sec:  u64,
 		pub usec: u64,
@@ -32,13 +52,220 @@ pub mod syscall {
 	impl TimeVal {
 		pub fn new() -> Self { Self::default() }
 	}
+
+	/// CPU times of `times`, in clock ticks
+	#[repr(C)]
+	#[derive(Copy, Clone, Debug, Default)]
+	pub struct Tms {
+		/// user time of the calling process
+		pub tms_utime:  i64,
+		/// system time of the calling process
+		pub tms_stime:  i64,
+		/// user time of the reaped children
+		pub tms_cutime: i64,
+		/// system time of the reaped children
+		pub tms_cstime: i64,
+	}
+
+	/// Resource usage of `getrusage`, only the CPU times and the context
+	/// switches are counted, the rest stays 0
+	#[repr(C)]
+	#[derive(Copy, Clone, Debug, Default)]
+	pub struct Rusage {
+		/// user time
+		pub ru_utime:    TimeVal,
+		/// system time
+		pub ru_stime:    TimeVal,
+		pub ru_maxrss:   i64,
+		pub ru_ixrss:    i64,
+		pub ru_idrss:    i64,
+		pub ru_isrss:    i64,
+		pub ru_minflt:   i64,
+		pub ru_majflt:   i64,
+		pub ru_nswap:    i64,
+		pub ru_inblock:  i64,
+		pub ru_oublock:  i64,
+		pub ru_msgsnd:   i64,
+		pub ru_msgrcv:   i64,
+		pub ru_nsignals: i64,
+		/// voluntary context switches
+		pub ru_nvcsw:    i64,
+		/// involuntary context switches
+		pub ru_nivcsw:   i64,
+	}
 }
 
 /// Fd
 pub mod fd {
+	pub const STDIN: usize = 0;
 	pub const STDOUT: usize = 1;
 }
 
 pub mod errno {
+	pub const ENOENT: isize = 2;
+	pub const ESRCH: isize = 3;
+	pub const EINTR: isize = 4;
+	pub const EIO: isize = 5;
+	pub const E2BIG: isize = 7;
+	pub const EBADF: isize = 9;
+	pub const ECHILD: isize = 10;
+	pub const ENOMEM: isize = 12;
+	pub const EFAULT: isize = 14;
+	pub const EEXIST: isize = 17;
 	pub const EINVAL: isize = 22;
+	pub const ENAMETOOLONG: isize = 36;
+	pub const ENOSYS: isize = 38;
+}
+
+/// Arguments of `setpriority` and `getrusage`
+pub mod resource {
+	/// `who` is a process id, 0 for the calling process
+	pub const PRIO_PROCESS: usize = 0;
+
+	/// the calling process
+	pub const RUSAGE_SELF: isize = 0;
+	/// the reaped children of the calling process and their reaped
+	/// descendants
+	pub const RUSAGE_CHILDREN: isize = -1;
+	/// the calling thread
+	pub const RUSAGE_THREAD: isize = 1;
+}
+
+/// Result of `task_info`
+pub mod task {
+	/// Syscall ids below this are counted by `task_info`
+	pub const MAX_SYSCALL_NUM: usize = 500;
+
+	#[repr(usize)]
+	#[derive(Copy, Clone, Debug, PartialEq)]
+	pub enum TaskStatus {
+		Ready,
+		Running,
+		/// waiting in the timer queue for its deadline, or in a wait queue
+		Sleeping,
+		/// stopped by a stop signal, off the ready queue until `SIGCONT`
+		Stopped,
+		/// exited, waiting to be reaped by the parent
+		Zombie,
+	}
+
+	#[repr(C)]
+	#[derive(Copy, Clone, Debug)]
+	pub struct TaskInfo {
+		pub status:        TaskStatus,
+		/// how often the task made each syscall, by id
+		pub syscall_times: [u32; MAX_SYSCALL_NUM],
+		/// milliseconds since the task was first scheduled
+		pub time:          usize,
+	}
+
+	impl Default for TaskInfo {
+		fn default() -> Self { Self { status: TaskStatus::Ready, syscall_times: [0; MAX_SYSCALL_NUM], time: 0 } }
+	}
+}
+
+/// Options of `wait4`
+pub mod wait {
+	/// Return at once if no child has exited
+	pub const WNOHANG: usize = 1;
+}
+
+/// Flags of `mmap` and `mprotect`
+pub mod mman {
+	pub const PROT_NONE: usize = 0;
+	pub const PROT_READ: usize = 1;
+	pub const PROT_WRITE: usize = 2;
+	pub const PROT_EXEC: usize = 4;
+
+	pub const MAP_SHARED: usize = 0x01;
+	pub const MAP_PRIVATE: usize = 0x02;
+	pub const MAP_FIXED: usize = 0x10;
+	pub const MAP_ANONYMOUS: usize = 0x20;
+
+	pub const MAP_FAILED: isize = -1;
+}
+
+/// Types of the auxiliary vector entries on the initial user stack
+pub mod auxv {
+	/// End of the vector
+	pub const AT_NULL: usize = 0;
+	/// Page size
+	pub const AT_PAGESZ: usize = 6;
+	/// Entry point of the program
+	pub const AT_ENTRY: usize = 9;
+	/// Pid of the task, not in Linux, numbered well past its types
+	pub const AT_PID: usize = 0x1000;
+}
+
+/// Signal numbers, `rt_sigaction` and `rt_sigprocmask`
+pub mod signal {
+	pub const SIGHUP: usize = 1;
+	pub const SIGINT: usize = 2;
+	pub const SIGQUIT: usize = 3;
+	pub const SIGILL: usize = 4;
+	pub const SIGTRAP: usize = 5;
+	pub const SIGABRT: usize = 6;
+	pub const SIGBUS: usize = 7;
+	pub const SIGFPE: usize = 8;
+	pub const SIGKILL: usize = 9;
+	pub const SIGUSR1: usize = 10;
+	pub const SIGSEGV: usize = 11;
+	pub const SIGUSR2: usize = 12;
+	pub const SIGPIPE: usize = 13;
+	pub const SIGALRM: usize = 14;
+	pub const SIGTERM: usize = 15;
+	pub const SIGSTKFLT: usize = 16;
+	pub const SIGCHLD: usize = 17;
+	pub const SIGCONT: usize = 18;
+	pub const SIGSTOP: usize = 19;
+	pub const SIGTSTP: usize = 20;
+	pub const SIGTTIN: usize = 21;
+	pub const SIGTTOU: usize = 22;
+	pub const SIGURG: usize = 23;
+	pub const SIGXCPU: usize = 24;
+	pub const SIGXFSZ: usize = 25;
+	pub const SIGVTALRM: usize = 26;
+	pub const SIGPROF: usize = 27;
+	pub const SIGWINCH: usize = 28;
+	pub const SIGIO: usize = 29;
+	pub const SIGPWR: usize = 30;
+	pub const SIGSYS: usize = 31;
+	/// Signals are numbered from 1 to `NSIG`, real-time signals included
+	pub const NSIG: usize = 64;
+
+	/// `sa_handler` taking the default action
+	pub const SIG_DFL: usize = 0;
+	/// `sa_handler` ignoring the signal
+	pub const SIG_IGN: usize = 1;
+
+	/// `sa_restorer` is set, the handler returns to it
+	pub const SA_RESTORER: usize = 0x0400_0000;
+	/// Do not block the signal while its handler runs
+	pub const SA_NODEFER: usize = 0x4000_0000;
+
+	pub const SIG_BLOCK: usize = 0;
+	pub const SIG_UNBLOCK: usize = 1;
+	pub const SIG_SETMASK: usize = 2;
+
+	/// Set of signals, signal `sig` is bit `sig - 1`
+	pub type SigSet = u64;
+
+	/// The bit of `sig` in a [`SigSet`]
+	pub const fn sigmask(sig: usize) -> SigSet { 1 << (sig - 1) }
+
+	/// Argument of `rt_sigaction`.
+	///
+	/// The handler is called with the signal number in `a0` and returns to
+	/// `restorer`, which has to call `rt_sigreturn`.
+	#[repr(C)]
+	#[derive(Copy, Clone, Debug, Default)]
+	pub struct SigAction {
+		/// `SIG_DFL`, `SIG_IGN` or the address of the handler
+		pub handler:  usize,
+		/// `SA_*` bits
+		pub flags:    usize,
+		pub restorer: usize,
+		/// signals blocked while the handler runs, besides the signal itself
+		pub mask:     SigSet,
+	}
 }
@@ -1,12 +1,20 @@
 // Batch
-pub const MAX_APP_NUM: usize = 16;
 pub const USER_STACK_SIZE: usize = 4096 * 2;
 pub const KERNEL_STACK_SIZE: usize = 4096 * 2;
-pub const APP_BASE_ADDRESS: usize = 0x80400000;
-pub const APP_SIZE_LIMIT: usize = 0x20000;
+
+/// 路径名（含结尾的 NUL）的最大长度
+pub const PATH_MAX: usize = 4096;
+/// `execve` 的参数与环境变量（含字符串和指针）的总长度上限，它们都放在用户栈上
+pub const ARG_MAX: usize = 4096;
 
 // Preemptive
 pub const TICKS_PER_SEC: u64 = 100;
+
+/// 新任务的默认优先级
+pub const DEFAULT_PRIORITY: usize = 16;
+/// 允许设置的最小优先级
+pub const MIN_PRIORITY: usize = 2;
+pub const MILLI_PER_SEC: u64 = 1_000;
 pub const MICRO_PER_SEC: u64 = 1_000_000;
 
 /// 物理页大小，十六进制表示方便地址转页号的计算(2^12=4096=0x1000)
@@ -16,5 +24,19 @@ pub const PAGE_SIZE_BITS: usize = 12;
 
 /// 内核堆大小
 pub const KERNEL_HEAP_SIZE: usize = 0x300000;
+/// 控制台输入缓冲区最多保存的字节数，其余的留在 SBI 中
+pub const CONSOLE_INPUT_MAX: usize = 4096;
+/// 关机时的汇总表最多列出的最近退出的任务数
+pub const MAX_EXIT_RECORDS: usize = 64;
+
+/// 用户地址空间（Sv39 低半部分）的上界
+pub const USER_SPACE_END: usize = 1 << 38;
+/// 未指定地址的 `mmap` 从这里开始寻找空闲区域
+pub const MMAP_BASE: usize = 0x10_0000_0000;
+
+/// 跳板页的虚拟地址，位于每个地址空间的最高页
+pub const TRAMPOLINE: usize = usize::MAX - PAGE_SIZE + 1;
+/// 应用 Trap 上下文所在页的虚拟地址，紧挨在跳板页之下
+pub const TRAP_CONTEXT: usize = TRAMPOLINE - PAGE_SIZE;
 
 pub use crate::boards::qemu::{MEMORY_END, MTIME_FREQUENCY_HZ};
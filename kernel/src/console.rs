@@ -1,6 +1,10 @@
+use alloc::collections::VecDeque;
 use core::fmt::{self, Write};
 
-use sbi_rt::console_write_byte;
+use lazy_static::lazy_static;
+use sbi_rt::{Console, Physical, console_read, console_write_byte, legacy::console_getchar, probe_extension};
+
+use crate::{config::CONSOLE_INPUT_MAX, sync::UPSafeCell, task::WaitQueue};
 
 struct Stdout;
 
@@ -15,6 +19,74 @@ impl Write for Stdout {
 
 pub fn print(args: fmt::Arguments) { Stdout.write_fmt(args).unwrap(); }
 
+lazy_static! {
+	/// whether the SBI implementation has the debug console extension
+	static ref HAS_DBCN: bool = probe_extension(Console).is_available();
+	/// console input taken from the SBI, until a reader moves it out
+	static ref INPUT: UPSafeCell<VecDeque<u8>> = unsafe { UPSafeCell::new(VecDeque::new()) };
+	/// tasks blocked in `read` on the console until some input arrives
+	static ref READERS: WaitQueue = WaitQueue::new();
+}
+
+/// Move the pending console input of the SBI into `buf`, returns the number
+/// of bytes read, 0 if there is nothing yet or the SBI failed.
+///
+/// Falls back to the legacy `console_getchar`, one byte at a time, if the
+/// SBI implementation has no debug console.
+fn read_sbi(buf: &mut [u8]) -> usize {
+	if *HAS_DBCN {
+		// the kernel space is identity mapped, the buffer address is physical
+		return console_read(Physical::new(buf.len(), buf.as_mut_ptr() as usize, 0)).into_result().unwrap_or(0);
+	}
+	match console_getchar() {
+		// -1 means there is no input
+		usize::MAX => 0,
+		c => {
+			buf[0] = c as u8;
+			1
+		}
+	}
+}
+
+/// Take the pending console input from the SBI and wake the readers if there
+/// is any, called on every timer tick.
+///
+/// At most [`CONSOLE_INPUT_MAX`] bytes are kept, the rest waits in the SBI
+/// until a reader makes room.
+pub fn poll_input() {
+	let mut buf = [0; 64];
+	let mut input = INPUT.exclusive_access();
+	while input.len() < CONSOLE_INPUT_MAX {
+		let room = (CONSOLE_INPUT_MAX - input.len()).min(buf.len());
+		let n = read_sbi(&mut buf[..room]);
+		if n == 0 {
+			break;
+		}
+		input.extend(&buf[..n]);
+	}
+	let arrived = !input.is_empty();
+	drop(input);
+	if arrived {
+		READERS.wake_all();
+	}
+}
+
+/// Move the console input into `buf`, returns the number of bytes read, 0 if
+/// there is nothing yet.
+pub fn read(buf: &mut [u8]) -> usize {
+	poll_input();
+	let mut input = INPUT.exclusive_access();
+	let n = buf.len().min(input.len());
+	for (dst, src) in buf.iter_mut().zip(input.drain(..n)) {
+		*dst = src;
+	}
+	n
+}
+
+/// Put current task to sleep until some console input arrives, then run
+/// next task.
+pub fn wait_input() { READERS.wait(); }
+
 #[macro_export]
 macro_rules! print {
     ($fmt: literal $(, $($arg: tt)+)?) => {
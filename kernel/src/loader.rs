@@ -1,40 +1,9 @@
-use core::arch::asm;
+use alloc::vec::Vec;
 
-use crate::{config::*, trap::context::TrapContext};
+use lazy_static::lazy_static;
 
-#[repr(align(4096))]
-#[derive(Copy, Clone)]
-struct KernelStack {
-	data: [u8; KERNEL_STACK_SIZE],
-}
-
-#[repr(align(4096))]
-#[derive(Copy, Clone)]
-struct UserStack {
-	data: [u8; USER_STACK_SIZE],
-}
-
-static KERNEL_STACK: [KernelStack; MAX_APP_NUM] = [KernelStack { data: [0; KERNEL_STACK_SIZE] }; MAX_APP_NUM];
-
-static USER_STACK: [UserStack; MAX_APP_NUM] = [UserStack { data: [0; USER_STACK_SIZE] }; MAX_APP_NUM];
-
-impl KernelStack {
-	fn get_sp(&self) -> usize { self.data.as_ptr() as usize + KERNEL_STACK_SIZE }
-
-	pub fn push_context(&self, trap_cx: TrapContext) -> usize {
-		let trap_cx_ptr = (self.get_sp() - core::mem::size_of::<TrapContext>()) as *mut TrapContext;
-		unsafe {
-			*trap_cx_ptr = trap_cx;
-		}
-		trap_cx_ptr as usize
-	}
-}
-
-impl UserStack {
-	fn get_sp(&self) -> usize { self.data.as_ptr() as usize + USER_STACK_SIZE }
-}
-
-pub fn load_apps() {
+/// Get the ELF image of app `app_id` embedded in the kernel data section.
+pub fn get_app_data(app_id: usize) -> &'static [u8] {
 	unsafe extern "C" {
 		fn _num_app();
 	}
@@ -42,26 +11,12 @@ pub fn load_apps() {
 	let num_app_ptr = _num_app as *const () as usize as *const usize;
 	let num_app = get_num_app();
 	let app_start = unsafe { core::slice::from_raw_parts(num_app_ptr.add(1), num_app + 1) };
-	// load apps
-	for i in 0..num_app {
-		let base_i = get_base_i(i);
-		// clear region
-		(base_i..base_i + APP_SIZE_LIMIT).for_each(|addr| unsafe { (addr as *mut u8).write_volatile(0) });
-		// load app from data section to memory
-		let src =
-			unsafe { core::slice::from_raw_parts(app_start[i] as *const u8, app_start[i + 1] - app_start[i]) };
-		let dst = unsafe { core::slice::from_raw_parts_mut(base_i as *mut u8, src.len()) };
-		dst.copy_from_slice(src);
-	}
-
+	assert!(app_id < num_app);
 	unsafe {
-		asm!("fence.i");
+		core::slice::from_raw_parts(app_start[app_id] as *const u8, app_start[app_id + 1] - app_start[app_id])
 	}
 }
 
-/// Get base address of app i.
-fn get_base_i(app_id: usize) -> usize { APP_BASE_ADDRESS + app_id * APP_SIZE_LIMIT }
-
 /// Get the total number of applications.
 pub fn get_num_app() -> usize {
 	unsafe extern "C" {
@@ -70,8 +25,24 @@ pub fn get_num_app() -> usize {
 	unsafe { (_num_app as *const () as usize as *const usize).read_volatile() }
 }
 
-/// get app info with entry and sp and save `TrapContext` in kernel stack
-pub fn init_app_cx(app_id: usize) -> usize {
-	KERNEL_STACK[app_id]
-		.push_context(TrapContext::app_init_context(get_base_i(app_id), USER_STACK[app_id].get_sp()))
+lazy_static! {
+	/// Names of the apps, in the order of their ELF images.
+	static ref APP_NAMES: Vec<&'static str> = {
+		unsafe extern "C" {
+			fn _app_names();
+		}
+		let mut start = _app_names as *const () as usize as *const u8;
+		(0..get_num_app())
+			.map(|_| unsafe {
+				let name = core::ffi::CStr::from_ptr(start as *const core::ffi::c_char);
+				start = start.add(name.count_bytes() + 1);
+				name.to_str().unwrap()
+			})
+			.collect()
+	};
+}
+
+/// Get the ELF image of the app called `name`.
+pub fn get_app_data_by_name(name: &str) -> Option<&'static [u8]> {
+	APP_NAMES.iter().position(|app_name| *app_name == name).map(get_app_data)
 }
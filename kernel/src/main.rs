@@ -1,9 +1,10 @@
 #![no_std]
 #![no_main]
 #![feature(step_trait)]
-// #![feature(alloc_error_handler)]
+#![feature(alloc_error_handler)]
+#![feature(fn_align)]
 
-// extern crate alloc;
+extern crate alloc;
 use core::{arch::global_asm, error};
 
 #[macro_use]
@@ -14,6 +15,7 @@ mod config;
 mod lang_items;
 mod loader;
 mod log;
+mod memory;
 mod sbi;
 mod stack_trace;
 mod sync;
@@ -51,10 +53,10 @@ pub fn rust_main() -> ! {
 	);
 	trace!(".bss [{:#x}, {:#x})", sbss as *const () as usize, ebss as *const () as usize);
 
+	memory::init();
 	trap::init();
-	loader::load_apps();
 	trap::enable_timer_interrupt();
 	sbi::set_next_trigger();
-	task::run_first_task();
-	panic!("Unreachable in rust_main!");
+	task::add_initproc();
+	task::run_tasks();
 }
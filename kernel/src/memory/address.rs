@@ -0,0 +1,200 @@
+//! Sv39 physical/virtual address and page number types.
+
+use core::{fmt::{self, Debug, Formatter}, iter::Step};
+
+use crate::{config::{PAGE_SIZE, PAGE_SIZE_BITS}, memory::page_table::PageTableEntry};
+
+/// 物理地址宽度
+const PA_WIDTH_SV39: usize = 56;
+/// 虚拟地址宽度
+const VA_WIDTH_SV39: usize = 39;
+/// 物理页号宽度
+const PPN_WIDTH_SV39: usize = PA_WIDTH_SV39 - PAGE_SIZE_BITS;
+/// 虚拟页号宽度
+const VPN_WIDTH_SV39: usize = VA_WIDTH_SV39 - PAGE_SIZE_BITS;
+
+/// Physical address
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct PhysAddr(pub usize);
+
+/// Virtual address
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct VirtAddr(pub usize);
+
+/// Physical page number
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct PhysPageNum(pub usize);
+
+/// Virtual page number
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct VirtPageNum(pub usize);
+
+impl Debug for VirtAddr {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { f.write_fmt(format_args!("VA:{:#x}", self.0)) }
+}
+
+impl Debug for VirtPageNum {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { f.write_fmt(format_args!("VPN:{:#x}", self.0)) }
+}
+
+impl Debug for PhysAddr {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { f.write_fmt(format_args!("PA:{:#x}", self.0)) }
+}
+
+impl Debug for PhysPageNum {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { f.write_fmt(format_args!("PPN:{:#x}", self.0)) }
+}
+
+impl From<usize> for PhysAddr {
+	fn from(v: usize) -> Self { Self(v & ((1 << PA_WIDTH_SV39) - 1)) }
+}
+
+impl From<usize> for PhysPageNum {
+	fn from(v: usize) -> Self { Self(v & ((1 << PPN_WIDTH_SV39) - 1)) }
+}
+
+impl From<usize> for VirtAddr {
+	fn from(v: usize) -> Self { Self(v & ((1 << VA_WIDTH_SV39) - 1)) }
+}
+
+impl From<usize> for VirtPageNum {
+	fn from(v: usize) -> Self { Self(v & ((1 << VPN_WIDTH_SV39) - 1)) }
+}
+
+impl From<PhysAddr> for usize {
+	fn from(v: PhysAddr) -> Self { v.0 }
+}
+
+impl From<PhysPageNum> for usize {
+	fn from(v: PhysPageNum) -> Self { v.0 }
+}
+
+impl From<VirtAddr> for usize {
+	/// Sv39 requires bits 63..39 to be a sign extension of bit 38.
+	fn from(v: VirtAddr) -> Self {
+		if v.0 >= (1 << (VA_WIDTH_SV39 - 1)) { v.0 | (!((1 << VA_WIDTH_SV39) - 1)) } else { v.0 }
+	}
+}
+
+impl From<VirtPageNum> for usize {
+	fn from(v: VirtPageNum) -> Self { v.0 }
+}
+
+impl VirtAddr {
+	/// Round down to the page containing this address.
+	pub fn floor(&self) -> VirtPageNum { VirtPageNum(self.0 / PAGE_SIZE) }
+
+	/// Round up to the first page not below this address.
+	pub fn ceil(&self) -> VirtPageNum {
+		if self.0 == 0 { VirtPageNum(0) } else { VirtPageNum((self.0 - 1 + PAGE_SIZE) / PAGE_SIZE) }
+	}
+
+	/// Offset inside the page.
+	pub fn page_offset(&self) -> usize { self.0 & (PAGE_SIZE - 1) }
+
+	/// Whether the address is page aligned.
+	pub fn aligned(&self) -> bool { self.page_offset() == 0 }
+}
+
+impl From<VirtAddr> for VirtPageNum {
+	fn from(v: VirtAddr) -> Self {
+		assert_eq!(v.page_offset(), 0);
+		v.floor()
+	}
+}
+
+impl From<VirtPageNum> for VirtAddr {
+	fn from(v: VirtPageNum) -> Self { Self(v.0 << PAGE_SIZE_BITS) }
+}
+
+impl PhysAddr {
+	/// Round down to the page containing this address.
+	pub fn floor(&self) -> PhysPageNum { PhysPageNum(self.0 / PAGE_SIZE) }
+
+	/// Round up to the first page not below this address.
+	pub fn ceil(&self) -> PhysPageNum {
+		if self.0 == 0 { PhysPageNum(0) } else { PhysPageNum((self.0 - 1 + PAGE_SIZE) / PAGE_SIZE) }
+	}
+
+	/// Offset inside the page.
+	pub fn page_offset(&self) -> usize { self.0 & (PAGE_SIZE - 1) }
+
+	/// Whether the address is page aligned.
+	pub fn aligned(&self) -> bool { self.page_offset() == 0 }
+
+	/// Get a mutable reference to a `T` stored at this physical address.
+	///
+	/// The kernel identity maps all physical memory, so the physical address
+	/// can be dereferenced directly.
+	pub fn get_mut<T>(&self) -> &'static mut T { unsafe { (self.0 as *mut T).as_mut().unwrap() } }
+}
+
+impl From<PhysAddr> for PhysPageNum {
+	fn from(v: PhysAddr) -> Self {
+		assert_eq!(v.page_offset(), 0);
+		v.floor()
+	}
+}
+
+impl From<PhysPageNum> for PhysAddr {
+	fn from(v: PhysPageNum) -> Self { Self(v.0 << PAGE_SIZE_BITS) }
+}
+
+impl VirtPageNum {
+	/// Split the page number into the three 9-bit indexes of the Sv39 page
+	/// table levels, from the root level down.
+	pub fn indexes(&self) -> [usize; 3] {
+		let mut vpn = self.0;
+		let mut idx = [0usize; 3];
+		for i in (0..3).rev() {
+			idx[i] = vpn & 511;
+			vpn >>= 9;
+		}
+		idx
+	}
+}
+
+impl PhysPageNum {
+	/// View the physical page as a page table.
+	pub fn get_pte_array(&self) -> &'static mut [PageTableEntry] {
+		let pa: PhysAddr = (*self).into();
+		unsafe { core::slice::from_raw_parts_mut(pa.0 as *mut PageTableEntry, 512) }
+	}
+
+	/// View the physical page as raw bytes.
+	pub fn get_bytes_array(&self) -> &'static mut [u8] {
+		let pa: PhysAddr = (*self).into();
+		unsafe { core::slice::from_raw_parts_mut(pa.0 as *mut u8, PAGE_SIZE) }
+	}
+
+	/// Get a mutable reference to a `T` stored at the beginning of the page.
+	pub fn get_mut<T>(&self) -> &'static mut T {
+		let pa: PhysAddr = (*self).into();
+		pa.get_mut()
+	}
+}
+
+/// Allow `Range<VirtPageNum>` to be iterated page by page.
+impl Step for VirtPageNum {
+	fn steps_between(start: &Self, end: &Self) -> (usize, Option<usize>) {
+		usize::steps_between(&start.0, &end.0)
+	}
+
+	fn forward_checked(start: Self, count: usize) -> Option<Self> {
+		usize::forward_checked(start.0, count).map(Self)
+	}
+
+	fn forward_overflowing(start: Self, count: usize) -> (Self, bool) {
+		let (vpn, overflow) = usize::forward_overflowing(start.0, count);
+		(Self(vpn), overflow)
+	}
+
+	fn backward_checked(start: Self, count: usize) -> Option<Self> {
+		usize::backward_checked(start.0, count).map(Self)
+	}
+
+	fn backward_overflowing(start: Self, count: usize) -> (Self, bool) {
+		let (vpn, overflow) = usize::backward_overflowing(start.0, count);
+		(Self(vpn), overflow)
+	}
+}
@@ -0,0 +1,100 @@
+//! Physical page frame allocator over `[ekernel, MEMORY_END)`.
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+
+use lazy_static::lazy_static;
+
+use crate::{config::MEMORY_END, memory::address::{PhysAddr, PhysPageNum}, sync::UPSafeCell};
+
+/// RAII handle of a physical frame, the frame goes back to the allocator
+/// when it is dropped.
+pub struct Frame {
+	pub ppn: PhysPageNum,
+}
+
+impl Frame {
+	/// Take ownership of `ppn` and clear the page.
+	fn new(ppn: PhysPageNum) -> Self {
+		ppn.get_bytes_array().fill(0);
+		Self { ppn }
+	}
+}
+
+impl Debug for Frame {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_fmt(format_args!("Frame:PPN={:#x}", self.ppn.0))
+	}
+}
+
+impl Drop for Frame {
+	fn drop(&mut self) { frame_dealloc(self.ppn); }
+}
+
+trait FrameAllocator {
+	fn alloc(&mut self) -> Option<PhysPageNum>;
+	fn dealloc(&mut self, ppn: PhysPageNum);
+}
+
+/// Hands out frames from `[current, end)`, and reuses frames given back in
+/// `recycled` first.
+pub struct StackFrameAllocator {
+	/// First page number of the never allocated range.
+	current:  usize,
+	/// End page number (exclusive) of the managed range.
+	end:      usize,
+	/// Page numbers that were allocated and then given back.
+	recycled: Vec<usize>,
+}
+
+impl StackFrameAllocator {
+	const fn new() -> Self { Self { current: 0, end: 0, recycled: Vec::new() } }
+
+	fn init(&mut self, l: PhysPageNum, r: PhysPageNum) {
+		self.current = l.0;
+		self.end = r.0;
+	}
+}
+
+impl FrameAllocator for StackFrameAllocator {
+	fn alloc(&mut self) -> Option<PhysPageNum> {
+		if let Some(ppn) = self.recycled.pop() {
+			Some(ppn.into())
+		} else if self.current == self.end {
+			None
+		} else {
+			self.current += 1;
+			Some((self.current - 1).into())
+		}
+	}
+
+	fn dealloc(&mut self, ppn: PhysPageNum) {
+		let ppn = ppn.0;
+		if ppn >= self.current || self.recycled.contains(&ppn) {
+			panic!("Frame ppn={:#x} has not been allocated!", ppn);
+		}
+		self.recycled.push(ppn);
+	}
+}
+
+lazy_static! {
+	static ref FRAME_ALLOCATOR: UPSafeCell<StackFrameAllocator> =
+		unsafe { UPSafeCell::new(StackFrameAllocator::new()) };
+}
+
+/// Hand the RAM between the end of the kernel image and `MEMORY_END` to the
+/// frame allocator.
+pub fn init_frame_allocator() {
+	unsafe extern "C" {
+		safe fn ekernel();
+	}
+	FRAME_ALLOCATOR
+		.exclusive_access()
+		.init(PhysAddr::from(ekernel as *const () as usize).ceil(), PhysAddr::from(MEMORY_END).floor());
+}
+
+/// Allocate a zeroed physical frame.
+pub fn frame_alloc() -> Option<Frame> { FRAME_ALLOCATOR.exclusive_access().alloc().map(Frame::new) }
+
+/// Give a frame back, called when a [`Frame`] is dropped.
+fn frame_dealloc(ppn: PhysPageNum) { FRAME_ALLOCATOR.exclusive_access().dealloc(ppn); }
@@ -0,0 +1,27 @@
+//! Kernel heap backed by a buddy system allocator.
+
+use core::alloc::Layout;
+
+use buddy_system_allocator::LockedHeap;
+
+use crate::config::KERNEL_HEAP_SIZE;
+
+/// Global allocator used by `alloc` in the kernel.
+#[global_allocator]
+static HEAP_ALLOCATOR: LockedHeap<32> = LockedHeap::empty();
+
+/// Memory the kernel heap allocates from, it lives in `.bss`.
+static mut HEAP_SPACE: [u8; KERNEL_HEAP_SIZE] = [0; KERNEL_HEAP_SIZE];
+
+/// Hand `HEAP_SPACE` to the global allocator.
+pub fn init_heap() {
+	unsafe {
+		HEAP_ALLOCATOR.lock().init(&raw mut HEAP_SPACE as usize, KERNEL_HEAP_SIZE);
+	}
+}
+
+/// Called when the kernel heap cannot satisfy an allocation.
+#[alloc_error_handler]
+pub fn handle_alloc_error(layout: Layout) -> ! {
+	panic!("Heap allocation error, layout = {:?}", layout);
+}
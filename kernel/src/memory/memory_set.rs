@@ -0,0 +1,633 @@
+//! Address spaces made of page-granular map areas.
+
+use alloc::{collections::btree_map::BTreeMap, sync::Arc, vec::Vec};
+use core::{arch::asm, ops::Range};
+
+use enumflags2::{BitFlags, bitflags};
+use lazy_static::lazy_static;
+use xmas_elf::{ElfFile, program::Type};
+
+use crate::{config::{MEMORY_END, MMAP_BASE, PAGE_SIZE, TRAMPOLINE, TRAP_CONTEXT, USER_SPACE_END, USER_STACK_SIZE}, memory::{address::{PhysAddr, PhysPageNum, VirtAddr, VirtPageNum}, frame_allocator::{Frame, frame_alloc}, page_table::{PTEFlag, PTEFlags, PageTable, PageTableEntry}}, sync::UPSafeCell, trace};
+
+unsafe extern "C" {
+	safe fn stext();
+	safe fn etext();
+	safe fn srodata();
+	safe fn erodata();
+	safe fn sdata();
+	safe fn edata();
+	safe fn sbss_with_stack();
+	safe fn ebss();
+	safe fn ekernel();
+	safe fn strampoline();
+}
+
+lazy_static! {
+	/// The kernel's address space.
+	pub static ref KERNEL_SPACE: UPSafeCell<MemorySet> = unsafe { UPSafeCell::new(MemorySet::new_kernel()) };
+}
+
+/// How the virtual pages of a [`MapArea`] get their physical frames.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MapType {
+	/// Virtual page number equals physical page number.
+	Identical,
+	/// Each virtual page gets a newly allocated frame.
+	Framed,
+	/// Like `Framed`, but a page only gets its frame when it is first
+	/// accessed, see [`MemorySet::handle_page_fault`].
+	Lazy,
+}
+
+/// Access permission of a [`MapArea`], a subset of the page table entry
+/// flags.
+#[bitflags]
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MapPerm {
+	R = 1 << 1,
+	W = 1 << 2,
+	X = 1 << 3,
+	U = 1 << 4,
+}
+
+pub type MapPermission = BitFlags<MapPerm>;
+
+/// A contiguous range of virtual pages with the same map type and
+/// permission.
+pub struct MapArea {
+	vpn_range:   Range<VirtPageNum>,
+	/// Frames backing a framed area, shared with the forked address spaces
+	/// until the first write, released with the last area holding them.
+	data_frames: BTreeMap<VirtPageNum, Arc<Frame>>,
+	map_type:    MapType,
+	map_perm:    MapPermission,
+}
+
+impl MapArea {
+	pub fn new(start_va: VirtAddr, end_va: VirtAddr, map_type: MapType, map_perm: MapPermission) -> Self {
+		Self { vpn_range: start_va.floor()..end_va.ceil(), data_frames: BTreeMap::new(), map_type, map_perm }
+	}
+
+	/// Copy the range, type and permission of `another`, without any frame.
+	fn from_another(another: &MapArea) -> Self {
+		Self {
+			vpn_range:   another.vpn_range.clone(),
+			data_frames: BTreeMap::new(),
+			map_type:    another.map_type,
+			map_perm:    another.map_perm,
+		}
+	}
+
+	/// Page table entry flags of `vpn`: a shared page of a writable area is
+	/// mapped read-only and copy-on-write.
+	fn pte_flags(&self, vpn: VirtPageNum) -> PTEFlags {
+		let flags = PTEFlags::from_bits_truncate(self.map_perm.bits() as u16);
+		match self.data_frames.get(&vpn) {
+			Some(frame) if Arc::strong_count(frame) > 1 && flags.contains(PTEFlag::W) => {
+				(flags & !PTEFlag::W) | PTEFlag::Cow
+			}
+			_ => flags,
+		}
+	}
+
+	/// Map `vpn` to its own frame, or to the same physical page for an
+	/// identical area. Returns `false` if there is no frame left.
+	fn map_one(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) -> bool {
+		let ppn = match self.map_type {
+			MapType::Identical => PhysPageNum(vpn.0),
+			MapType::Framed | MapType::Lazy => {
+				let Some(frame) = frame_alloc() else {
+					return false;
+				};
+				let ppn = frame.ppn;
+				self.data_frames.insert(vpn, Arc::new(frame));
+				ppn
+			}
+		};
+		page_table.map(vpn, ppn, self.pte_flags(vpn));
+		true
+	}
+
+	/// Map `frame` of another address space at `vpn`, both sides become
+	/// copy-on-write if the area is writable.
+	fn map_shared(&mut self, page_table: &mut PageTable, vpn: VirtPageNum, frame: Arc<Frame>) {
+		let ppn = frame.ppn;
+		self.data_frames.insert(vpn, frame);
+		page_table.map(vpn, ppn, self.pte_flags(vpn));
+	}
+
+	/// Give `vpn` a frame of its own after a write to the copy-on-write page,
+	/// the frame is only copied if it is still shared.
+	///
+	/// Returns `false` if there is no frame left for the copy.
+	fn copy_on_write(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) -> bool {
+		let frame = self.data_frames.get_mut(&vpn).unwrap();
+		if Arc::strong_count(frame) > 1 {
+			let Some(new_frame) = frame_alloc() else {
+				return false;
+			};
+			new_frame.ppn.get_bytes_array().copy_from_slice(frame.ppn.get_bytes_array());
+			*frame = Arc::new(new_frame);
+			page_table.unmap(vpn);
+			page_table.map(vpn, frame.ppn, self.pte_flags(vpn));
+		} else {
+			page_table.set_flags(vpn, self.pte_flags(vpn));
+		}
+		true
+	}
+
+	fn unmap_one(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) {
+		match self.map_type {
+			MapType::Identical => {}
+			MapType::Framed => {
+				self.data_frames.remove(&vpn);
+			}
+			MapType::Lazy => {
+				// pages never touched have nothing to unmap
+				if self.data_frames.remove(&vpn).is_none() {
+					return;
+				}
+			}
+		}
+		page_table.unmap(vpn);
+	}
+
+	/// Map every page of the area, except for a lazy area whose pages are
+	/// mapped on demand.
+	fn map(&mut self, page_table: &mut PageTable) {
+		if self.map_type == MapType::Lazy {
+			return;
+		}
+		for vpn in self.vpn_range.clone() {
+			assert!(self.map_one(page_table, vpn), "no frame for map area");
+		}
+	}
+
+	fn unmap(&mut self, page_table: &mut PageTable) {
+		for vpn in self.vpn_range.clone() {
+			self.unmap_one(page_table, vpn);
+		}
+	}
+
+	/// Move the end of the area down to `new_end`, unmapping the pages past
+	/// it.
+	fn shrink_to(&mut self, page_table: &mut PageTable, new_end: VirtPageNum) {
+		for vpn in new_end..self.vpn_range.end {
+			self.unmap_one(page_table, vpn);
+		}
+		self.vpn_range.end = new_end;
+	}
+
+	/// Move the end of the area up to `new_end`, mapping the new pages unless
+	/// the area is lazy.
+	fn append_to(&mut self, page_table: &mut PageTable, new_end: VirtPageNum) {
+		if self.map_type != MapType::Lazy {
+			for vpn in self.vpn_range.end..new_end {
+				assert!(self.map_one(page_table, vpn), "no frame for map area");
+			}
+		}
+		self.vpn_range.end = new_end;
+	}
+
+	/// Split the area at `at`, the area keeps `[start, at)` and the returned
+	/// area takes `[at, end)` with its frames.
+	fn split_off(&mut self, at: VirtPageNum) -> MapArea {
+		let area = MapArea {
+			vpn_range:   at..self.vpn_range.end,
+			data_frames: self.data_frames.split_off(&at),
+			map_type:    self.map_type,
+			map_perm:    self.map_perm,
+		};
+		self.vpn_range.end = at;
+		area
+	}
+
+	/// Change the permission of the area and of every page mapped in it.
+	fn set_perm(&mut self, page_table: &mut PageTable, map_perm: MapPermission) {
+		self.map_perm = map_perm;
+		for vpn in self.data_frames.keys() {
+			page_table.set_flags(*vpn, self.pte_flags(*vpn));
+		}
+	}
+
+	/// Copy `data` into the area, starting `offset` bytes into its first page,
+	/// the area must be framed and mapped in `page_table` already.
+	fn copy_data(&self, page_table: &PageTable, offset: usize, data: &[u8]) {
+		assert_eq!(self.map_type, MapType::Framed);
+		let mut data = data;
+		let mut offset = offset;
+		for vpn in self.vpn_range.clone() {
+			if data.is_empty() {
+				break;
+			}
+			let len = (PAGE_SIZE - offset).min(data.len());
+			let page = page_table.translate(vpn).unwrap().ppn().get_bytes_array();
+			page[offset..offset + len].copy_from_slice(&data[..len]);
+			data = &data[len..];
+			offset = 0;
+		}
+	}
+}
+
+/// An address space: a page table and the areas mapped in it.
+pub struct MemorySet {
+	page_table: PageTable,
+	areas:      Vec<MapArea>,
+	/// index of the heap area in `areas`, kept up to date as areas are
+	/// removed
+	heap:       Option<usize>,
+}
+
+impl MemorySet {
+	pub fn new_bare() -> Self { Self { page_table: PageTable::new(), areas: Vec::new(), heap: None } }
+
+	/// Map `map_area`, then fill it with `data` if there is any, starting
+	/// `offset` bytes into its first page.
+	fn push(&mut self, mut map_area: MapArea, data: Option<(usize, &[u8])>) {
+		map_area.map(&mut self.page_table);
+		if let Some((offset, data)) = data {
+			map_area.copy_data(&self.page_table, offset, data);
+		}
+		self.areas.push(map_area);
+	}
+
+	/// Value to be written into `satp` to enable this address space.
+	pub fn token(&self) -> usize { self.page_table.token() }
+
+	/// Map the trampoline page, it is not tracked by any area because every
+	/// address space maps it to the same frame in the kernel image.
+	fn map_trampoline(&mut self) {
+		self.page_table.map(
+			VirtAddr::from(TRAMPOLINE).into(),
+			PhysAddr::from(strampoline as *const () as usize).into(),
+			PTEFlag::R | PTEFlag::X,
+		);
+	}
+
+	/// Identity map the kernel image and the rest of the physical memory.
+	fn new_kernel() -> Self {
+		let mut memory_set = Self::new_bare();
+		memory_set.map_trampoline();
+		let sections: [(&str, usize, usize, MapPermission); 5] = [
+			(".text", stext as *const () as usize, etext as *const () as usize, MapPerm::R | MapPerm::X),
+			(".rodata", srodata as *const () as usize, erodata as *const () as usize, MapPerm::R.into()),
+			(".data", sdata as *const () as usize, edata as *const () as usize, MapPerm::R | MapPerm::W),
+			(".bss", sbss_with_stack as *const () as usize, ebss as *const () as usize, MapPerm::R | MapPerm::W),
+			("physical memory", ekernel as *const () as usize, MEMORY_END, MapPerm::R | MapPerm::W),
+		];
+		for (name, start, end, perm) in sections {
+			trace!("mapping {} [{:#x}, {:#x})", name, start, end);
+			memory_set.push(MapArea::new(start.into(), end.into(), MapType::Identical, perm), None);
+		}
+		memory_set
+	}
+
+	/// Build the address space of an app from its ELF image: map every
+	/// `PT_LOAD` segment with its permissions, a user stack above the highest
+	/// segment, an empty heap right above the stack, the trampoline and the
+	/// `TrapContext` page below it.
+	///
+	/// Returns the address space, the user stack top, which is also the heap
+	/// bottom, and the entry point.
+	pub fn from_elf(elf_data: &[u8]) -> (Self, usize, usize) {
+		let mut memory_set = Self::new_bare();
+		memory_set.map_trampoline();
+		let elf = ElfFile::new(elf_data).expect("invalid app ELF");
+		assert_eq!(elf.header.pt1.magic, [0x7f, b'E', b'L', b'F'], "invalid app ELF magic");
+		let mut max_end_vpn = VirtPageNum(0);
+		for ph in elf.program_iter() {
+			if ph.get_type() != Ok(Type::Load) {
+				continue;
+			}
+			let start_va: VirtAddr = (ph.virtual_addr() as usize).into();
+			let end_va: VirtAddr = ((ph.virtual_addr() + ph.mem_size()) as usize).into();
+			let mut map_perm: MapPermission = MapPerm::U.into();
+			let ph_flags = ph.flags();
+			if ph_flags.is_read() {
+				map_perm |= MapPerm::R;
+			}
+			if ph_flags.is_write() {
+				map_perm |= MapPerm::W;
+			}
+			if ph_flags.is_execute() {
+				map_perm |= MapPerm::X;
+			}
+			// pages holding file data are loaded now, the pages past them are
+			// bss only and get zeroed frames on demand
+			let file_end_va: VirtAddr = ((ph.virtual_addr() + ph.file_size()) as usize).into();
+			let map_area = MapArea::new(start_va, file_end_va, MapType::Framed, map_perm);
+			let lazy_start_vpn = map_area.vpn_range.end.max(start_va.floor());
+			max_end_vpn = max_end_vpn.max(end_va.ceil());
+			let file_range = ph.offset() as usize..(ph.offset() + ph.file_size()) as usize;
+			// a segment may start in the middle of its first page
+			memory_set.push(map_area, Some((start_va.page_offset(), &elf_data[file_range])));
+			if lazy_start_vpn < end_va.ceil() {
+				memory_set.push(MapArea::new(lazy_start_vpn.into(), end_va, MapType::Lazy, map_perm), None);
+			}
+		}
+		// leave a guard page between the image and the user stack
+		let user_stack_bottom = usize::from(VirtAddr::from(max_end_vpn)) + PAGE_SIZE;
+		let user_stack_top = user_stack_bottom + USER_STACK_SIZE;
+		memory_set.push(
+			MapArea::new(
+				user_stack_bottom.into(),
+				user_stack_top.into(),
+				MapType::Lazy,
+				MapPerm::R | MapPerm::W | MapPerm::U,
+			),
+			None,
+		);
+		// grown and shrunk by `brk`
+		memory_set.heap = Some(memory_set.areas.len());
+		memory_set.push(
+			MapArea::new(
+				user_stack_top.into(),
+				user_stack_top.into(),
+				MapType::Lazy,
+				MapPerm::R | MapPerm::W | MapPerm::U,
+			),
+			None,
+		);
+		memory_set.push(
+			MapArea::new(TRAP_CONTEXT.into(), TRAMPOLINE.into(), MapType::Framed, MapPerm::R | MapPerm::W),
+			None,
+		);
+		(memory_set, user_stack_top, elf.header.pt2.entry_point() as usize)
+	}
+
+	/// Look up the page table entry of `vpn`.
+	pub fn translate(&self, vpn: VirtPageNum) -> Option<PageTableEntry> { self.page_table.translate(vpn) }
+
+	/// Shrink the heap area so it ends at `new_end`.
+	///
+	/// Returns `false` if there is no heap area.
+	pub fn shrink_heap_to(&mut self, new_end: VirtAddr) -> bool {
+		let Some(heap) = self.heap else {
+			return false;
+		};
+		self.areas[heap].shrink_to(&mut self.page_table, new_end.ceil());
+		true
+	}
+
+	/// Grow the heap area so it ends at `new_end`.
+	///
+	/// Returns `false` if there is no heap area, or the grown heap would
+	/// overlap another area.
+	pub fn append_heap_to(&mut self, new_end: VirtAddr) -> bool {
+		let Some(heap) = self.heap else {
+			return false;
+		};
+		let (start_vpn, new_end_vpn) = (self.areas[heap].vpn_range.start, new_end.ceil());
+		// tell the heap apart by index, an area mapped at the bottom of a still
+		// empty heap starts where the heap does
+		let overlapped =
+			self.areas.iter().enumerate().any(|(idx, area)| {
+				idx != heap && area.vpn_range.start < new_end_vpn && start_vpn < area.vpn_range.end
+			});
+		if overlapped {
+			return false;
+		}
+		self.areas[heap].append_to(&mut self.page_table, new_end_vpn);
+		true
+	}
+
+	/// Whether no area overlaps `vpn_range`.
+	fn is_free(&self, vpn_range: &Range<VirtPageNum>) -> bool {
+		!self
+			.areas
+			.iter()
+			.any(|area| area.vpn_range.start < vpn_range.end && vpn_range.start < area.vpn_range.end)
+	}
+
+	/// Find `pages` free pages below `USER_SPACE_END`, at `hint` if it is free
+	/// or else the lowest free range from `MMAP_BASE` up.
+	pub fn find_free_range(&self, hint: Option<VirtPageNum>, pages: usize) -> Option<VirtPageNum> {
+		let limit = VirtAddr::from(USER_SPACE_END).floor();
+		let fits = |start: VirtPageNum| start.0.checked_add(pages).is_some_and(|end| end <= limit.0);
+		if let Some(hint) = hint
+			&& fits(hint)
+			&& self.is_free(&(hint..VirtPageNum(hint.0 + pages)))
+		{
+			return Some(hint);
+		}
+		let mut start = VirtAddr::from(MMAP_BASE).floor();
+		while fits(start) {
+			let end = VirtPageNum(start.0 + pages);
+			match self
+				.areas
+				.iter()
+				.filter(|area| area.vpn_range.start < end && start < area.vpn_range.end)
+				.map(|area| area.vpn_range.end)
+				.max()
+			{
+				Some(area_end) => start = area_end,
+				None => return Some(start),
+			}
+		}
+		None
+	}
+
+	/// Add a lazy user area over `vpn_range`, returns `false` if the range is
+	/// not free.
+	pub fn insert_lazy_area(&mut self, vpn_range: Range<VirtPageNum>, map_perm: MapPermission) -> bool {
+		if !self.is_free(&vpn_range) {
+			return false;
+		}
+		self.push(
+			MapArea::new(vpn_range.start.into(), vpn_range.end.into(), MapType::Lazy, map_perm | MapPerm::U),
+			None,
+		);
+		true
+	}
+
+	/// Map a framed area over `[start_va, end_va)`, the range must be free.
+	pub fn insert_framed_area(&mut self, start_va: VirtAddr, end_va: VirtAddr, map_perm: MapPermission) {
+		self.push(MapArea::new(start_va, end_va, MapType::Framed, map_perm), None);
+	}
+
+	/// Unmap the area starting at `start_vpn` and give its frames back.
+	pub fn remove_area_with_start_vpn(&mut self, start_vpn: VirtPageNum) {
+		if let Some(idx) = self.areas.iter().position(|area| area.vpn_range.start == start_vpn) {
+			let mut area = self.areas.swap_remove(idx);
+			area.unmap(&mut self.page_table);
+			if self.heap == Some(idx) {
+				self.heap = None;
+			} else if self.heap == Some(self.areas.len()) {
+				self.heap = Some(idx);
+			}
+		}
+	}
+
+	/// Split the area containing `vpn` so that no area crosses it.
+	fn split_at(&mut self, vpn: VirtPageNum) {
+		if let Some(area) =
+			self.areas.iter_mut().find(|area| area.vpn_range.start < vpn && vpn < area.vpn_range.end)
+		{
+			let tail = area.split_off(vpn);
+			self.areas.push(tail);
+		}
+	}
+
+	/// Unmap every user page in `vpn_range`, splitting the areas it cuts
+	/// through.
+	pub fn remove_range(&mut self, vpn_range: Range<VirtPageNum>) {
+		self.split_at(vpn_range.start);
+		self.split_at(vpn_range.end);
+		// the heap is only dropped once it has pages in the range
+		let covered = |area: &MapArea| {
+			area.map_perm.contains(MapPerm::U)
+				&& !area.vpn_range.is_empty()
+				&& vpn_range.start <= area.vpn_range.start
+				&& area.vpn_range.end <= vpn_range.end
+		};
+		self.heap = self
+			.heap
+			.filter(|&heap| !covered(&self.areas[heap]))
+			.map(|heap| heap - self.areas[..heap].iter().filter(|area| covered(area)).count());
+		let page_table = &mut self.page_table;
+		self.areas.extract_if(.., |area| covered(area)).for_each(|mut area| area.unmap(page_table));
+	}
+
+	/// Change the permission of every user page in `vpn_range` to
+	/// `map_perm`.
+	///
+	/// Returns `false` and changes nothing if part of the range is not mapped
+	/// by a user area.
+	pub fn protect_range(&mut self, vpn_range: Range<VirtPageNum>, map_perm: MapPermission) -> bool {
+		let mut covered: Vec<_> = self
+			.areas
+			.iter()
+			.filter(|area| area.vpn_range.start < vpn_range.end && vpn_range.start < area.vpn_range.end)
+			.map(|area| (area.vpn_range.clone(), area.map_perm.contains(MapPerm::U)))
+			.collect();
+		covered.sort_by_key(|(range, _)| range.start);
+		let mut next = vpn_range.start;
+		for (range, user) in covered {
+			if !user || range.start > next {
+				return false;
+			}
+			next = range.end;
+		}
+		if next < vpn_range.end {
+			return false;
+		}
+		self.split_at(vpn_range.start);
+		self.split_at(vpn_range.end);
+		for area in self.areas.iter_mut() {
+			if vpn_range.start <= area.vpn_range.start && area.vpn_range.end <= vpn_range.end {
+				area.set_perm(&mut self.page_table, map_perm | MapPerm::U);
+			}
+		}
+		true
+	}
+
+	/// Build a copy of a user address space for a forked task.
+	///
+	/// User frames are shared and the writable ones turn copy-on-write in both
+	/// address spaces, only the kernel-private pages such as the `TrapContext`
+	/// are copied right away.
+	pub fn from_existed_user(user_space: &mut MemorySet) -> MemorySet {
+		let mut memory_set = Self::new_bare();
+		memory_set.map_trampoline();
+		// areas are copied in order, so the heap keeps its index
+		memory_set.heap = user_space.heap;
+		for area in user_space.areas.iter_mut() {
+			let mut new_area = MapArea::from_another(area);
+			if area.map_perm.contains(MapPerm::U) {
+				for (vpn, frame) in area.data_frames.iter() {
+					new_area.map_shared(&mut memory_set.page_table, *vpn, frame.clone());
+				}
+				// the parent's pages are shared now as well
+				area.set_perm(&mut user_space.page_table, area.map_perm);
+				memory_set.areas.push(new_area);
+			} else {
+				memory_set.push(new_area, None);
+				for vpn in area.vpn_range.clone() {
+					let src = user_space.page_table.translate(vpn).unwrap().ppn();
+					let dst = memory_set.page_table.translate(vpn).unwrap().ppn();
+					dst.get_bytes_array().copy_from_slice(src.get_bytes_array());
+				}
+			}
+		}
+		memory_set
+	}
+
+	/// Resolve a page fault at `va` caused by an `access`: back the page with
+	/// a frame if it belongs to a lazy area and has not been touched yet, or
+	/// give it a private copy on the first write to a copy-on-write page.
+	///
+	/// Returns `false` if the fault is a genuine illegal access, or there is
+	/// no frame left to resolve it.
+	pub fn handle_page_fault(&mut self, va: VirtAddr, access: MapPerm) -> bool {
+		let vpn = va.floor();
+		let Some(area) = self.areas.iter_mut().find(|area| area.vpn_range.contains(&vpn)) else {
+			return false;
+		};
+		if !area.map_perm.contains(access | MapPerm::U) {
+			return false;
+		}
+		if area.data_frames.contains_key(&vpn) {
+			let cow = self.page_table.translate(vpn).is_some_and(|pte| pte.flags().contains(PTEFlag::Cow));
+			if access != MapPerm::W || !cow {
+				return false;
+			}
+			area.copy_on_write(&mut self.page_table, vpn)
+		} else if area.map_type == MapType::Lazy {
+			area.map_one(&mut self.page_table, vpn)
+		} else {
+			false
+		}
+	}
+
+	/// Physical page backing the user page `vpn` for an `access` the kernel
+	/// makes on behalf of the app.
+	///
+	/// Lazy and copy-on-write pages are resolved as if the app faulted on
+	/// them. Returns `None` if the app itself could not make the access.
+	pub fn translate_user(&mut self, vpn: VirtPageNum, access: MapPerm) -> Option<PhysPageNum> {
+		let required = PTEFlags::from_bits_truncate((access | MapPerm::U).bits() as u16) | PTEFlag::V;
+		let allowed = |pte: &PageTableEntry| pte.flags().contains(required);
+		if let Some(pte) = self.page_table.translate(vpn).filter(allowed) {
+			return Some(pte.ppn());
+		}
+		if !self.handle_page_fault(vpn.into(), access) {
+			return None;
+		}
+		self.page_table.translate(vpn).filter(allowed).map(|pte| pte.ppn())
+	}
+
+	/// Copy `data` into the user memory at `va` as if the app wrote it,
+	/// returns `false` if the app could not.
+	pub fn copy_to_user(&mut self, va: usize, data: &[u8]) -> bool {
+		let mut offset = 0;
+		while offset < data.len() {
+			let va = VirtAddr::from(va + offset);
+			let Some(ppn) = self.translate_user(va.floor(), MapPerm::W) else {
+				return false;
+			};
+			let len = (PAGE_SIZE - va.page_offset()).min(data.len() - offset);
+			ppn.get_bytes_array()[va.page_offset()..va.page_offset() + len]
+				.copy_from_slice(&data[offset..offset + len]);
+			offset += len;
+		}
+		true
+	}
+
+	/// Unmap every area and give its frames back, the page table itself is
+	/// kept until the memory set is dropped.
+	pub fn recycle_data_pages(&mut self) {
+		for area in self.areas.iter_mut() {
+			area.unmap(&mut self.page_table);
+		}
+		self.areas.clear();
+	}
+
+	/// Switch `satp` to this address space and flush the TLB.
+	pub fn activate(&self) {
+		let satp = self.token();
+		unsafe {
+			asm!("csrw satp, {}", "sfence.vma", in(reg) satp);
+		}
+	}
+}
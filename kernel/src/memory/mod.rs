@@ -0,0 +1,21 @@
+//! Memory management: Sv39 address types, frame allocation, the kernel heap
+//! and per-task address spaces.
+
+mod address;
+mod frame_allocator;
+mod heap_allocator;
+mod memory_set;
+mod page_table;
+mod user_ptr;
+
+pub use address::{PhysPageNum, VirtAddr, VirtPageNum};
+pub use memory_set::{KERNEL_SPACE, MapPerm, MapPermission, MemorySet};
+pub use user_ptr::{UserPtr, UserSlice, read_c_str};
+
+/// Initialize the heap and frame allocators, then turn on paging with the
+/// kernel address space.
+pub fn init() {
+	heap_allocator::init_heap();
+	frame_allocator::init_frame_allocator();
+	KERNEL_SPACE.exclusive_access().activate();
+}
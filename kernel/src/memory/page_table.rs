@@ -0,0 +1,150 @@
+//! RISC-V Sv39 three-level page table.
+
+use alloc::{vec, vec::Vec};
+
+use enumflags2::{BitFlags, bitflags};
+
+use crate::memory::{address::{PhysPageNum, VirtPageNum}, frame_allocator::{Frame, frame_alloc}};
+
+/// Page table entry flag bits.
+#[bitflags]
+#[repr(u16)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PTEFlag {
+	/// Valid
+	V   = 1 << 0,
+	/// Readable
+	R   = 1 << 1,
+	/// Writable
+	W   = 1 << 2,
+	/// Executable
+	X   = 1 << 3,
+	/// Accessible in U mode
+	U   = 1 << 4,
+	/// Global
+	G   = 1 << 5,
+	/// Accessed
+	A   = 1 << 6,
+	/// Dirty
+	D   = 1 << 7,
+	/// Copy-on-write, in the bits reserved for software: the page is writable
+	/// by its area but shared, the first write copies it
+	Cow = 1 << 8,
+}
+
+pub type PTEFlags = BitFlags<PTEFlag>;
+
+/// Sv39 page table entry: `[53:10]` PPN, `[9:8]` reserved for software,
+/// `[7:0]` flags.
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct PageTableEntry {
+	pub bits: usize,
+}
+
+impl PageTableEntry {
+	pub fn new(ppn: PhysPageNum, flags: PTEFlags) -> Self {
+		Self { bits: (ppn.0 << 10) | flags.bits() as usize }
+	}
+
+	pub fn empty() -> Self { Self { bits: 0 } }
+
+	pub fn ppn(&self) -> PhysPageNum { ((self.bits >> 10) & ((1usize << 44) - 1)).into() }
+
+	pub fn flags(&self) -> PTEFlags { PTEFlags::from_bits_truncate(self.bits as u16) }
+
+	pub fn is_valid(&self) -> bool { self.flags().contains(PTEFlag::V) }
+
+	pub fn readable(&self) -> bool { self.flags().contains(PTEFlag::R) }
+
+	pub fn writable(&self) -> bool { self.flags().contains(PTEFlag::W) }
+
+	pub fn executable(&self) -> bool { self.flags().contains(PTEFlag::X) }
+}
+
+/// A page table, `frames` owns every frame used by its nodes so they are
+/// released together with the table.
+pub struct PageTable {
+	root_ppn: PhysPageNum,
+	frames:   Vec<Frame>,
+}
+
+impl PageTable {
+	pub fn new() -> Self {
+		let frame = frame_alloc().expect("no frame for page table root");
+		Self { root_ppn: frame.ppn, frames: vec![frame] }
+	}
+
+	/// Find the leaf entry of `vpn`, creating missing intermediate nodes.
+	fn find_pte_create(&mut self, vpn: VirtPageNum) -> Option<&mut PageTableEntry> {
+		let idxs = vpn.indexes();
+		let mut ppn = self.root_ppn;
+		for (i, idx) in idxs.iter().enumerate() {
+			let pte = &mut ppn.get_pte_array()[*idx];
+			if i == 2 {
+				return Some(pte);
+			}
+			if !pte.is_valid() {
+				let frame = frame_alloc()?;
+				*pte = PageTableEntry::new(frame.ppn, PTEFlag::V.into());
+				self.frames.push(frame);
+			}
+			ppn = pte.ppn();
+		}
+		None
+	}
+
+	/// Find the leaf entry of `vpn` without creating anything.
+	fn find_pte(&self, vpn: VirtPageNum) -> Option<&mut PageTableEntry> {
+		let idxs = vpn.indexes();
+		let mut ppn = self.root_ppn;
+		for (i, idx) in idxs.iter().enumerate() {
+			let pte = &mut ppn.get_pte_array()[*idx];
+			if i == 2 {
+				return Some(pte);
+			}
+			if !pte.is_valid() {
+				return None;
+			}
+			ppn = pte.ppn();
+		}
+		None
+	}
+
+	/// Map `vpn` to `ppn`, see [`PageTable::set_flags`] for `flags` without
+	/// any access permission.
+	pub fn map(&mut self, vpn: VirtPageNum, ppn: PhysPageNum, flags: PTEFlags) {
+		let pte = self.find_pte_create(vpn).expect("no frame for page table node");
+		assert!(pte.bits == 0, "vpn {:?} is mapped before mapping", vpn);
+		*pte = PageTableEntry::new(ppn, Self::leaf_flags(flags));
+	}
+
+	pub fn unmap(&mut self, vpn: VirtPageNum) {
+		let pte = self.find_pte(vpn).expect("unmap a vpn without page table node");
+		// a page without any access permission is mapped but not valid
+		assert!(pte.bits != 0, "vpn {:?} is not mapped before unmapping", vpn);
+		*pte = PageTableEntry::empty();
+	}
+
+	/// Replace the flags of the mapped `vpn` and keep its frame.
+	///
+	/// Without any of R/W/X the entry is left invalid, so every access to the
+	/// page faults.
+	pub fn set_flags(&mut self, vpn: VirtPageNum, flags: PTEFlags) {
+		let pte = self.find_pte(vpn).expect("set flags of a vpn without page table node");
+		assert!(pte.bits != 0, "vpn {:?} is not mapped before setting flags", vpn);
+		*pte = PageTableEntry::new(pte.ppn(), Self::leaf_flags(flags));
+	}
+
+	/// `flags` of a leaf entry, valid only if it grants some access because
+	/// a valid entry without R/W/X points to the next level.
+	fn leaf_flags(flags: PTEFlags) -> PTEFlags {
+		if flags.intersects(PTEFlag::R | PTEFlag::W | PTEFlag::X) { flags | PTEFlag::V } else { flags }
+	}
+
+	/// Look up the leaf entry of `vpn`.
+	pub fn translate(&self, vpn: VirtPageNum) -> Option<PageTableEntry> { self.find_pte(vpn).map(|pte| *pte) }
+
+	/// Value to be written into `satp` to enable this table in Sv39 mode.
+	pub fn token(&self) -> usize { (8usize << 60) | self.root_ppn.0 }
+}
@@ -0,0 +1,125 @@
+//! Checked access to the memory of the current task, for syscalls.
+//!
+//! Every access translates the user addresses through the task's address
+//! space and fails with `-EFAULT` if the task could not make the same access
+//! itself.
+
+use alloc::{string::String, vec::Vec};
+use core::{marker::PhantomData, mem::MaybeUninit};
+
+use config::errno::{EFAULT, ENAMETOOLONG};
+
+use crate::{config::{PAGE_SIZE, USER_SPACE_END}, memory::{MapPerm, VirtAddr}, task::with_current_memory_set};
+
+/// A user pointer to a `T`, which may cross a page boundary.
+///
+/// `T` must be plain data that is valid for any bit pattern.
+pub struct UserPtr<T> {
+	addr:    usize,
+	_marker: PhantomData<*mut T>,
+}
+
+impl<T: Copy> UserPtr<T> {
+	pub fn new(ptr: *const T) -> Self { Self { addr: ptr as usize, _marker: PhantomData } }
+
+	pub fn is_null(&self) -> bool { self.addr == 0 }
+
+	fn as_slice(&self) -> UserSlice { UserSlice::new(self.addr as *const u8, size_of::<T>()) }
+
+	/// Read the `T` from user memory.
+	pub fn read(&self) -> Result<T, isize> {
+		let mut value = MaybeUninit::<T>::uninit();
+		let bytes = unsafe { core::slice::from_raw_parts_mut(value.as_mut_ptr() as *mut u8, size_of::<T>()) };
+		self.as_slice().read_into(bytes)?;
+		Ok(unsafe { value.assume_init() })
+	}
+
+	/// Write `value` to user memory.
+	pub fn write(&self, value: T) -> Result<(), isize> {
+		let bytes = unsafe { core::slice::from_raw_parts(&value as *const T as *const u8, size_of::<T>()) };
+		self.as_slice().write(bytes)
+	}
+}
+
+/// A user buffer of `len` bytes.
+pub struct UserSlice {
+	addr: usize,
+	len:  usize,
+}
+
+impl UserSlice {
+	pub fn new(ptr: *const u8, len: usize) -> Self { Self { addr: ptr as usize, len } }
+
+	/// Kernel views of the pages the buffer spans, checked for `access`
+	/// before any of them is used.
+	fn pages(&self, access: MapPerm) -> Result<Vec<&'static mut [u8]>, isize> {
+		let end = self.addr.checked_add(self.len).filter(|end| *end <= USER_SPACE_END).ok_or(-EFAULT)?;
+		with_current_memory_set(|memory_set| {
+			let mut pages = Vec::new();
+			let mut start = self.addr;
+			while start < end {
+				let va = VirtAddr::from(start);
+				let ppn = memory_set.translate_user(va.floor(), access).ok_or(-EFAULT)?;
+				let len = (PAGE_SIZE - va.page_offset()).min(end - start);
+				pages.push(&mut ppn.get_bytes_array()[va.page_offset()..va.page_offset() + len]);
+				start += len;
+			}
+			Ok(pages)
+		})
+	}
+
+	/// Copy the buffer into `dst`, which must be as long as the buffer.
+	pub fn read_into(&self, dst: &mut [u8]) -> Result<(), isize> {
+		assert_eq!(dst.len(), self.len);
+		let mut offset = 0;
+		for page in self.pages(MapPerm::R)? {
+			dst[offset..offset + page.len()].copy_from_slice(page);
+			offset += page.len();
+		}
+		Ok(())
+	}
+
+	/// Copy the buffer out of user memory.
+	pub fn read(&self) -> Result<Vec<u8>, isize> {
+		let mut data = alloc::vec![0; self.len];
+		self.read_into(&mut data)?;
+		Ok(data)
+	}
+
+	/// Copy `src`, which must be as long as the buffer, into the buffer.
+	pub fn write(&self, src: &[u8]) -> Result<(), isize> {
+		assert_eq!(src.len(), self.len);
+		let mut offset = 0;
+		for page in self.pages(MapPerm::W)? {
+			page.copy_from_slice(&src[offset..offset + page.len()]);
+			offset += page.len();
+		}
+		Ok(())
+	}
+}
+
+/// Read the NUL terminated string at `ptr`, at most `max_len` bytes long
+/// without the NUL.
+///
+/// Invalid UTF-8 is replaced, fails with `-ENAMETOOLONG` if there is no NUL
+/// in time.
+pub fn read_c_str(ptr: *const u8, max_len: usize) -> Result<String, isize> {
+	let mut bytes = Vec::new();
+	let mut addr = ptr as usize;
+	while bytes.len() <= max_len {
+		// read up to the end of the page, the next page may not be mapped
+		let len = PAGE_SIZE - VirtAddr::from(addr).page_offset();
+		let chunk = UserSlice::new(addr as *const u8, len).read()?;
+		if let Some(nul) = chunk.iter().position(|&b| b == 0) {
+			bytes.extend_from_slice(&chunk[..nul]);
+			return if bytes.len() <= max_len {
+				Ok(String::from_utf8_lossy(&bytes).into_owned())
+			} else {
+				Err(-ENAMETOOLONG)
+			};
+		}
+		bytes.extend_from_slice(&chunk);
+		addr += len;
+	}
+	Err(-ENAMETOOLONG)
+}
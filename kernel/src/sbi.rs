@@ -13,27 +13,11 @@ pub fn shutdown(failure: bool) -> ! {
 	unreachable!()
 }
 
-/// Sleep for the specified number of nanoseconds
-/// Uses the mtime register (10MHz tick → 100ns per tick)
-pub fn sleep_ns(ns: u64) {
-	// one tick = 100 ns
-	let cycles = ns.div_ceil(100);
-	sleep_ticks(cycles);
-}
-
-fn sleep_ticks(ticks: u64) {
-	let start = time::read();
-	let target = start.wrapping_add(ticks as usize);
-
-	// Handle wrapping case
-	if target < start {
-		while time::read() >= start {}
-	}
-	while time::read() < target {}
-}
-
 pub fn set_next_trigger() {
 	set_timer(time::read() as u64 + MTIME_FREQUENCY_HZ / TICKS_PER_SEC).expect("set_timer error");
 }
 
+/// Current value of the `time` CSR, in `MTIME_FREQUENCY_HZ` ticks.
+pub fn get_time() -> u64 { time::read() as u64 }
+
 pub fn get_time_us() -> u64 { time::read() as u64 / (MTIME_FREQUENCY_HZ / MICRO_PER_SEC) }
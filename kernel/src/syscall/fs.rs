@@ -1,18 +1,59 @@
-use config::fd::STDOUT;
+use alloc::{string::String, vec};
 
-use crate::print;
+use config::{errno::{EBADF, EINTR}, fd::{STDIN, STDOUT}};
 
-/// write buf of length `len`  to a file with `fd`
+use crate::{config::PAGE_SIZE, console, memory::UserSlice, task::current_interrupted};
+
+/// read at most `len` bytes from a file with `fd` into `buf`, the task
+/// blocks until some input is there or a signal interrupts it
+pub fn sys_read(fd: usize, buf: *mut u8, len: usize) -> isize {
+	match fd {
+		STDIN => {
+			if len == 0 {
+				return 0;
+			}
+			let mut data = vec![0; len.min(PAGE_SIZE)];
+			let n = loop {
+				match console::read(&mut data) {
+					0 if current_interrupted() => return -EINTR,
+					0 => console::wait_input(),
+					n => break n,
+				}
+			};
+			match UserSlice::new(buf, n).write(&data[..n]) {
+				Ok(()) => n as isize,
+				Err(errno) => errno,
+			}
+		}
+		_ => -EBADF,
+	}
+}
+
+/// write buf of length `len`  to a file with `fd`, a page at a time
+///
+/// A UTF-8 character cut by the end of a page is held back until the rest of
+/// it is read. Returns the number of bytes written, which is short if part of
+/// the buffer cannot be read.
 pub fn sys_write(fd: usize, buf: *const u8, len: usize) -> isize {
 	match fd {
 		STDOUT => {
-			let slice = unsafe { core::slice::from_raw_parts(buf, len) };
-			let str = core::str::from_utf8(slice).expect("sys_write not utf8 string");
-			print!("{}", str);
+			let mut data = vec![0; len.min(PAGE_SIZE)];
+			let (mut written, mut pending) = (0, 0);
+			while written < len {
+				let n = (len - written).min(data.len() - pending);
+				let end = pending + n;
+				if let Err(errno) = UserSlice::new(buf.wrapping_add(written), n).read_into(&mut data[pending..end]) {
+					print!("{}", String::from_utf8_lossy(&data[..pending]));
+					return if written > 0 { written as isize } else { errno };
+				}
+				written += n;
+				pending =
+					if written < len { data[..end].utf8_chunks().last().map_or(0, |c| c.invalid().len()) } else { 0 };
+				print!("{}", String::from_utf8_lossy(&data[..end - pending]));
+				data.copy_within(end - pending..end, 0);
+			}
 			len as isize
 		}
-		_ => {
-			panic!("Unsupported fd in sys_write!");
-		}
+		_ => -EBADF,
 	}
 }
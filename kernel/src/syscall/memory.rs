@@ -0,0 +1,105 @@
+use core::ops::Range;
+
+use config::{errno::{EEXIST, EINVAL, ENOMEM}, mman::*};
+
+use crate::{config::{PAGE_SIZE, USER_SPACE_END}, memory::{MapPerm, MapPermission, VirtAddr, VirtPageNum}, task::with_current_memory_set};
+
+/// Turn `PROT_*` bits into a map permission, `None` if there are unknown
+/// bits.
+///
+/// RISC-V has no write-only pages, `PROT_WRITE` implies `PROT_READ` like on
+/// Linux.
+fn prot_to_perm(prot: usize) -> Option<MapPermission> {
+	if prot & !(PROT_READ | PROT_WRITE | PROT_EXEC) != 0 {
+		return None;
+	}
+	let mut perm = MapPermission::empty();
+	if prot & (PROT_READ | PROT_WRITE) != 0 {
+		perm |= MapPerm::R;
+	}
+	if prot & PROT_WRITE != 0 {
+		perm |= MapPerm::W;
+	}
+	if prot & PROT_EXEC != 0 {
+		perm |= MapPerm::X;
+	}
+	Some(perm)
+}
+
+/// Pages spanned by `[addr, addr + len)`, `None` unless `addr` is page
+/// aligned, `len` is not zero and the range is in the user address space.
+fn user_vpn_range(addr: usize, len: usize) -> Option<Range<VirtPageNum>> {
+	if !addr.is_multiple_of(PAGE_SIZE) || len == 0 {
+		return None;
+	}
+	let end = addr.checked_add(len).filter(|end| *end <= USER_SPACE_END)?;
+	Some(VirtAddr::from(addr).floor()..VirtAddr::from(end).ceil())
+}
+
+/// Map `len` bytes of anonymous private memory with `prot`.
+///
+/// Without `MAP_FIXED`, a non-zero `addr` is only a hint. With `MAP_FIXED`
+/// the mapping is placed exactly at `addr`, which must not overlap an
+/// existing mapping (`-EEXIST`), existing mappings are never replaced.
+///
+/// Returns the start address of the mapping.
+pub fn sys_mmap(addr: usize, len: usize, prot: usize, flags: usize, _fd: usize, offset: usize) -> isize {
+	let Some(perm) = prot_to_perm(prot) else {
+		return -EINVAL;
+	};
+	if flags & !(MAP_PRIVATE | MAP_FIXED | MAP_ANONYMOUS) != 0
+		|| flags & (MAP_PRIVATE | MAP_ANONYMOUS) != MAP_PRIVATE | MAP_ANONYMOUS
+		|| !offset.is_multiple_of(PAGE_SIZE)
+		|| !addr.is_multiple_of(PAGE_SIZE)
+		|| len == 0
+	{
+		return -EINVAL;
+	}
+	let Some(pages) = len.checked_next_multiple_of(PAGE_SIZE).map(|len| len / PAGE_SIZE) else {
+		return -ENOMEM;
+	};
+	with_current_memory_set(|memory_set| {
+		let start = if flags & MAP_FIXED != 0 {
+			let Some(vpn_range) = user_vpn_range(addr, len) else {
+				return -EINVAL;
+			};
+			vpn_range.start
+		} else {
+			let hint = (addr != 0).then(|| VirtAddr::from(addr).floor());
+			let Some(start) = memory_set.find_free_range(hint, pages) else {
+				return -ENOMEM;
+			};
+			start
+		};
+		if !memory_set.insert_lazy_area(start..VirtPageNum(start.0 + pages), perm) {
+			return -EEXIST;
+		}
+		usize::from(VirtAddr::from(start)) as isize
+	})
+}
+
+/// Unmap the pages in `[addr, addr + len)`, pages that are not mapped are
+/// skipped.
+pub fn sys_munmap(addr: usize, len: usize) -> isize {
+	let Some(vpn_range) = user_vpn_range(addr, len) else {
+		return -EINVAL;
+	};
+	with_current_memory_set(|memory_set| memory_set.remove_range(vpn_range));
+	0
+}
+
+/// Change the protection of the pages in `[addr, addr + len)` to `prot`.
+///
+/// Returns `-ENOMEM` if part of the range is not mapped.
+pub fn sys_mprotect(addr: usize, len: usize, prot: usize) -> isize {
+	let Some(perm) = prot_to_perm(prot) else {
+		return -EINVAL;
+	};
+	if len == 0 && addr.is_multiple_of(PAGE_SIZE) {
+		return 0;
+	}
+	let Some(vpn_range) = user_vpn_range(addr, len) else {
+		return -EINVAL;
+	};
+	if with_current_memory_set(|memory_set| memory_set.protect_range(vpn_range, perm)) { 0 } else { -ENOMEM }
+}
@@ -1,19 +1,44 @@
 mod fs;
+mod memory;
 mod process;
+mod signal;
 mod time;
 
-use config::syscall::*;
+use config::{errno::ENOSYS, signal::{SigAction, SigSet}, syscall::*, task::TaskInfo};
 
-use crate::syscall::{fs::*, process::*, time::*};
+use crate::{syscall::{fs::*, memory::*, process::*, signal::*, time::*}, task::record_current_syscall};
 
 /// handle syscall exception with `sycall_id` and other arguments
-pub fn syscall(syscall_id: usize, args: [usize; 3]) -> isize {
+pub fn syscall(syscall_id: usize, args: [usize; 6]) -> isize {
+	record_current_syscall(syscall_id);
 	match syscall_id {
+		READ => sys_read(args[0], args[1] as *mut u8, args[2]),
 		WRITE => sys_write(args[0], args[1] as *const u8, args[2]),
 		EXIT => sys_exit(args[0] as i32),
 		NANOSLEEP => sys_nanosleep(args[0] as *const KernelTimespec, args[1] as *mut KernelTimespec),
 		YIELD => sys_yield(),
+		KILL => sys_kill(args[0] as isize, args[1]),
+		RT_SIGACTION => {
+			sys_rt_sigaction(args[0], args[1] as *const SigAction, args[2] as *mut SigAction, args[3])
+		}
+		RT_SIGPROCMASK => sys_rt_sigprocmask(args[0], args[1] as *const SigSet, args[2] as *mut SigSet, args[3]),
+		RT_SIGRETURN => sys_rt_sigreturn(),
 		GETTIMEOFDAY => sys_gettimeofday(args[0] as *mut TimeVal, args[1]),
-		_ => panic!("Unsupported syscall_id: {}", syscall_id),
+		GETPID => sys_getpid(),
+		GETPPID => sys_getppid(),
+		GETTID => sys_gettid(),
+		SETPRIORITY => sys_setpriority(args[0], args[1], args[2] as isize),
+		TIMES => sys_times(args[0] as *mut Tms),
+		GETRUSAGE => sys_getrusage(args[0] as isize, args[1] as *mut Rusage),
+		BRK => sys_brk(args[0]),
+		MUNMAP => sys_munmap(args[0], args[1]),
+		FORK => sys_fork(),
+		EXECVE => sys_execve(args[0] as *const u8, args[1] as *const usize, args[2] as *const usize),
+		MMAP => sys_mmap(args[0], args[1], args[2], args[3], args[4], args[5]),
+		MPROTECT => sys_mprotect(args[0], args[1], args[2]),
+		SPAWN => sys_spawn(args[0] as *const u8, args[1] as *const usize),
+		TASK_INFO => sys_task_info(args[0] as *mut TaskInfo),
+		WAIT4 => sys_wait4(args[0] as isize, args[1] as *mut i32, args[2], args[3]),
+		_ => -ENOSYS,
 	}
 }
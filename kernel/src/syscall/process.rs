@@ -1,11 +1,14 @@
-use config::syscall::TimeVal;
+use alloc::{string::String, vec::Vec};
+use core::mem::offset_of;
 
-use crate::{config::MICRO_PER_SEC, sbi::get_time_us, task::{exit_current_and_run_next, suspend_current_and_run_next}, trace};
+use config::{errno::{E2BIG, ECHILD, EINTR, EINVAL, ENAMETOOLONG, ENOENT, ESRCH}, resource::{PRIO_PROCESS, RUSAGE_CHILDREN, RUSAGE_SELF, RUSAGE_THREAD}, syscall::{Rusage, TimeVal, Tms}, task::{TaskInfo, TaskStatus}, wait::WNOHANG};
+
+use crate::{config::{ARG_MAX, MICRO_PER_SEC, MILLI_PER_SEC, MIN_PRIORITY, MTIME_FREQUENCY_HZ, PATH_MAX}, loader::get_app_data_by_name, memory::{UserPtr, UserSlice, read_c_str}, sbi::{get_time, get_time_us}, task::{add_task, change_program_brk, current_interrupted, current_task, exit_current_and_run_next, nap_current_and_run_next, pid2task, set_task_priority, suspend_current_and_run_next, to_clock_ticks, to_time_val}, trace};
 
 /// task exits and submit an exit code
 pub fn sys_exit(exit_code: i32) -> ! {
 	trace!("Application exited with code {}", exit_code);
-	exit_current_and_run_next();
+	exit_current_and_run_next(exit_code);
 	panic!("Unreachable in sys_exit!");
 }
 
@@ -17,8 +20,259 @@ pub fn sys_yield() -> isize {
 
 pub fn sys_gettimeofday(ts: *mut TimeVal, _tz: usize) -> isize {
 	let us = get_time_us();
-	unsafe {
-		*ts = TimeVal { sec: us / MICRO_PER_SEC, usec: us % MICRO_PER_SEC };
+	match UserPtr::new(ts).write(TimeVal { sec: us / MICRO_PER_SEC, usec: us % MICRO_PER_SEC }) {
+		Ok(()) => 0,
+		Err(errno) => errno,
+	}
+}
+
+/// Store the CPU times of current task and of its reaped children in `tms`,
+/// unless it is null.
+///
+/// Returns the clock ticks since boot.
+pub fn sys_times(tms: *mut Tms) -> isize {
+	let now = get_time();
+	let task = current_task().unwrap();
+	let mut inner = task.inner_exclusive_access();
+	inner.usage.charge_kernel(now);
+	let times = Tms {
+		tms_utime:  to_clock_ticks(inner.usage.user_time),
+		tms_stime:  to_clock_ticks(inner.usage.kernel_time),
+		tms_cutime: to_clock_ticks(inner.children_usage.user_time),
+		tms_cstime: to_clock_ticks(inner.children_usage.kernel_time),
+	};
+	drop(inner);
+	let tms = UserPtr::new(tms);
+	if !tms.is_null()
+		&& let Err(errno) = tms.write(times)
+	{
+		return errno;
+	}
+	to_clock_ticks(now) as isize
+}
+
+/// Store the resource usage of `who` in `usage`: `RUSAGE_SELF` or
+/// `RUSAGE_THREAD` for current task, `RUSAGE_CHILDREN` for its reaped
+/// children.
+///
+/// Only the CPU times and the context switches are filled in.
+pub fn sys_getrusage(who: isize, usage: *mut Rusage) -> isize {
+	let task = current_task().unwrap();
+	let mut inner = task.inner_exclusive_access();
+	inner.usage.charge_kernel(get_time());
+	let cpu = match who {
+		RUSAGE_SELF | RUSAGE_THREAD => inner.usage,
+		RUSAGE_CHILDREN => inner.children_usage,
+		_ => return -EINVAL,
+	};
+	drop(inner);
+	let rusage = Rusage {
+		ru_utime: to_time_val(cpu.user_time),
+		ru_stime: to_time_val(cpu.kernel_time),
+		ru_nvcsw: cpu.voluntary_switches as i64,
+		ru_nivcsw: cpu.involuntary_switches as i64,
+		..Default::default()
+	};
+	match UserPtr::new(usage).write(rusage) {
+		Ok(()) => 0,
+		Err(errno) => errno,
+	}
+}
+
+/// Store the status, the syscall counts and the milliseconds since it was
+/// first scheduled of current task in `ti`.
+pub fn sys_task_info(ti: *mut TaskInfo) -> isize {
+	let task = current_task().unwrap();
+	let inner = task.inner_exclusive_access();
+	let syscall_times: Vec<u8> = inner.syscall_times.iter().flat_map(|count| count.to_ne_bytes()).collect();
+	let time = (get_time() - inner.first_run.unwrap()) / (MTIME_FREQUENCY_HZ / MILLI_PER_SEC);
+	let status = inner.task_status;
+	drop(inner);
+	// written field by field, the counts are too large to go through the
+	// kernel stack
+	let field = |offset: usize| (ti as usize).wrapping_add(offset);
+	let result = UserPtr::new(field(offset_of!(TaskInfo, status)) as *const TaskStatus)
+		.write(status)
+		.and_then(|()| {
+			UserSlice::new(field(offset_of!(TaskInfo, syscall_times)) as *const u8, syscall_times.len())
+				.write(&syscall_times)
+		})
+		.and_then(|()| UserPtr::new(field(offset_of!(TaskInfo, time)) as *const usize).write(time as usize));
+	match result {
+		Ok(()) => 0,
+		Err(errno) => errno,
+	}
+}
+
+/// Returns the pid of current task.
+pub fn sys_getpid() -> isize { current_task().unwrap().getpid() as isize }
+
+/// Returns the pid of the parent of current task, 0 for `initproc`.
+pub fn sys_getppid() -> isize {
+	let parent =
+		current_task().unwrap().inner_exclusive_access().parent.as_ref().and_then(|parent| parent.upgrade());
+	parent.map_or(0, |parent| parent.getpid() as isize)
+}
+
+/// Returns the thread id of current task, every task has a single thread
+/// whose id is the pid.
+pub fn sys_gettid() -> isize { sys_getpid() }
+
+/// Set the scheduling priority of the task `who`, 0 for current task,
+/// to `prio`.
+///
+/// Unlike a Linux nice value a larger `prio` gives a larger share of the
+/// CPU, it must be at least `MIN_PRIORITY`. Only `PRIO_PROCESS` is
+/// supported for `which`.
+pub fn sys_setpriority(which: usize, who: usize, prio: isize) -> isize {
+	if which != PRIO_PROCESS || prio < MIN_PRIORITY as isize {
+		return -EINVAL;
 	}
+	let task = if who == 0 { current_task() } else { pid2task(who) };
+	let Some(task) = task else {
+		return -ESRCH;
+	};
+	set_task_priority(&task, prio as usize);
 	0
 }
+
+/// Set the program break to `addr`, `addr == 0` only queries it.
+///
+/// Like Linux, returns the new program break on success and the current one
+/// on failure.
+pub fn sys_brk(addr: usize) -> isize { change_program_brk(addr) as isize }
+
+/// Duplicate current task, the child shares the parent's memory
+/// copy-on-write.
+///
+/// Returns the pid of the child in the parent and 0 in the child.
+pub fn sys_fork() -> isize {
+	let new_task = current_task().unwrap().fork();
+	let new_pid = new_task.getpid();
+	add_task(new_task);
+	new_pid as isize
+}
+
+/// Replace the current program with the app called `path`, passing it the
+/// null terminated string arrays `argv` and `envp`, either may be null.
+///
+/// Fails with `-E2BIG` if the strings and their pointers on the new user
+/// stack would take more than `ARG_MAX` bytes.
+pub fn sys_execve(path: *const u8, argv: *const usize, envp: *const usize) -> isize {
+	let path = match read_c_str(path, PATH_MAX - 1) {
+		Ok(path) => path,
+		Err(errno) => return errno,
+	};
+	let mut total = 0;
+	let args = match read_str_array(argv, &mut total) {
+		Ok(args) => args,
+		Err(errno) => return errno,
+	};
+	let envs = match read_str_array(envp, &mut total) {
+		Ok(envs) => envs,
+		Err(errno) => return errno,
+	};
+	match get_app_data_by_name(&path) {
+		Some(data) => {
+			current_task().unwrap().exec(&path, data, &args, &envs);
+			0
+		}
+		None => -ENOENT,
+	}
+}
+
+/// Start the app called `path` as a child, passing it the null terminated
+/// string array `argv`, which may be null, and an empty environment.
+///
+/// Unlike `fork` and `execve` the child gets a fresh address space, nothing
+/// of the caller's memory is copied.
+///
+/// Returns the pid of the child.
+pub fn sys_spawn(path: *const u8, argv: *const usize) -> isize {
+	let path = match read_c_str(path, PATH_MAX - 1) {
+		Ok(path) => path,
+		Err(errno) => return errno,
+	};
+	let args = match read_str_array(argv, &mut 0) {
+		Ok(args) => args,
+		Err(errno) => return errno,
+	};
+	let Some(data) = get_app_data_by_name(&path) else {
+		return -ENOENT;
+	};
+	let child = current_task().unwrap().spawn(&path, data, &args);
+	let pid = child.getpid();
+	add_task(child);
+	pid as isize
+}
+
+/// Read the strings of the null terminated pointer array `array`, adding the
+/// bytes they and their pointers take on the user stack to `total`.
+fn read_str_array(array: *const usize, total: &mut usize) -> Result<Vec<String>, isize> {
+	let mut strings = Vec::new();
+	if array.is_null() {
+		return Ok(strings);
+	}
+	loop {
+		let ptr = UserPtr::new(array.wrapping_add(strings.len())).read()?;
+		if ptr == 0 {
+			return Ok(strings);
+		}
+		let s = read_c_str(ptr as *const u8, ARG_MAX.saturating_sub(*total))
+			.map_err(|errno| if errno == -ENAMETOOLONG { -E2BIG } else { errno })?;
+		*total += s.len() + 1 + size_of::<usize>();
+		if *total > ARG_MAX {
+			return Err(-E2BIG);
+		}
+		strings.push(s);
+	}
+}
+
+/// Wait for a child to exit and reap it.
+///
+/// `pid` of -1 (or any other non-positive value, there are no process
+/// groups) waits for any child. The exit status is stored in `wstatus` the
+/// way Linux encodes it, `rusage` is ignored.
+///
+/// Returns the pid of the reaped child, 0 if `options` has `WNOHANG` and no
+/// child has exited yet, `-ECHILD` if there is no such child, or `-EINTR`
+/// if a signal came first.
+pub fn sys_wait4(pid: isize, wstatus: *mut i32, options: usize, _rusage: usize) -> isize {
+	let matches = |child_pid: usize| pid <= 0 || pid as usize == child_pid;
+	loop {
+		let task = current_task().unwrap();
+		let mut inner = task.inner_exclusive_access();
+		if !inner.children.iter().any(|child| matches(child.getpid())) {
+			return -ECHILD;
+		}
+		let zombie = inner
+			.children
+			.iter()
+			.position(|child| matches(child.getpid()) && child.inner_exclusive_access().is_zombie());
+		if let Some(idx) = zombie {
+			// the child is released once this last reference is dropped
+			let child = inner.children.remove(idx);
+			let child_inner = child.inner_exclusive_access();
+			inner.children_usage.add(&child_inner.usage);
+			inner.children_usage.add(&child_inner.children_usage);
+			let status = child_inner.exit_reason.wait_status();
+			drop(child_inner);
+			drop(inner);
+			if !wstatus.is_null()
+				&& let Err(errno) = UserPtr::new(wstatus).write(status)
+			{
+				return errno;
+			}
+			return child.getpid() as isize;
+		}
+		if options & WNOHANG != 0 {
+			return 0;
+		}
+		drop(inner);
+		drop(task);
+		if current_interrupted() {
+			return -EINTR;
+		}
+		nap_current_and_run_next();
+	}
+}
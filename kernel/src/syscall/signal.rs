@@ -0,0 +1,125 @@
+use core::mem::size_of;
+
+use config::{errno::{EINVAL, ESRCH}, signal::*};
+
+use crate::{memory::UserPtr, task::{current_task, pid2task, wake_task}};
+
+/// Send `sig` to the task `pid`, `sig` 0 only checks that the task exists.
+///
+/// There are no process groups, `pid` must be positive.
+pub fn sys_kill(pid: isize, sig: usize) -> isize {
+	if pid <= 0 || sig > NSIG {
+		return -EINVAL;
+	}
+	let Some(task) = pid2task(pid as usize) else {
+		return -ESRCH;
+	};
+	if sig != 0 {
+		task.inner_exclusive_access().signals.raise(sig);
+		// a sleeping task checks whether the signal interrupts it, a stopped
+		// one whether it is continued or killed
+		wake_task(&task);
+	}
+	0
+}
+
+/// Change the action of `sig` to `*act` unless `act` is null, and store the
+/// old action in `oldact` unless it is null.
+///
+/// A handler has to return to a `restorer` set with `SA_RESTORER`, there is
+/// no trampoline in the kernel to fall back on.
+pub fn sys_rt_sigaction(
+	sig: usize,
+	act: *const SigAction,
+	oldact: *mut SigAction,
+	sigsetsize: usize,
+) -> isize {
+	if sigsetsize != size_of::<SigSet>() || !(1..=NSIG).contains(&sig) {
+		return -EINVAL;
+	}
+	let act = UserPtr::new(act);
+	// read the user memory before borrowing the task, the access borrows it
+	let new_action = if act.is_null() {
+		None
+	} else if sig == SIGKILL || sig == SIGSTOP {
+		return -EINVAL;
+	} else {
+		match act.read() {
+			Ok(action)
+				if action.handler != SIG_DFL
+					&& action.handler != SIG_IGN
+					&& (action.flags & SA_RESTORER == 0 || action.restorer == 0) =>
+			{
+				return -EINVAL;
+			}
+			Ok(action) => Some(action),
+			Err(errno) => return errno,
+		}
+	};
+	let task = current_task().unwrap();
+	let mut inner = task.inner_exclusive_access();
+	let old_action = inner.signals.action(sig);
+	if let Some(action) = new_action {
+		inner.signals.set_action(sig, action);
+	}
+	drop(inner);
+	let oldact = UserPtr::new(oldact);
+	if !oldact.is_null()
+		&& let Err(errno) = oldact.write(old_action)
+	{
+		return errno;
+	}
+	0
+}
+
+/// Change the blocked signals by `how` with `*set` unless `set` is null, and
+/// store the old blocked set in `oldset` unless it is null.
+pub fn sys_rt_sigprocmask(how: usize, set: *const SigSet, oldset: *mut SigSet, sigsetsize: usize) -> isize {
+	if sigsetsize != size_of::<SigSet>() {
+		return -EINVAL;
+	}
+	let set = UserPtr::new(set);
+	let set = if set.is_null() {
+		None
+	} else {
+		match set.read() {
+			Ok(set) => Some(set),
+			Err(errno) => return errno,
+		}
+	};
+	let task = current_task().unwrap();
+	let mut inner = task.inner_exclusive_access();
+	let old_set = inner.signals.blocked;
+	if let Some(set) = set {
+		let blocked = match how {
+			SIG_BLOCK => old_set | set,
+			SIG_UNBLOCK => old_set & !set,
+			SIG_SETMASK => set,
+			_ => return -EINVAL,
+		};
+		inner.signals.set_blocked(blocked);
+	}
+	drop(inner);
+	let oldset = UserPtr::new(oldset);
+	if !oldset.is_null()
+		&& let Err(errno) = oldset.write(old_set)
+	{
+		return errno;
+	}
+	0
+}
+
+/// Return from a signal handler to where the task was interrupted.
+///
+/// Returns the interrupted `a0`, so that the trap handler leaves it as it
+/// was, or `-EINVAL` if no handler is running.
+pub fn sys_rt_sigreturn() -> isize {
+	let task = current_task().unwrap();
+	let mut inner = task.inner_exclusive_access();
+	let Some((trap_cx, blocked)) = inner.signals.take_saved() else {
+		return -EINVAL;
+	};
+	*inner.get_trap_cx() = trap_cx;
+	inner.signals.set_blocked(blocked);
+	trap_cx.x[10] as isize
+}
@@ -1,34 +1,57 @@
-use config::{errno::EINVAL, syscall::KernelTimespec};
+use config::{errno::{EINTR, EINVAL}, syscall::KernelTimespec};
 
-use crate::sbi::sleep_ns;
+use crate::{config::MTIME_FREQUENCY_HZ, memory::UserPtr, sbi::get_time, task::{current_interrupted, sleep_current_and_run_next}};
+
+const NANO_PER_SEC: u64 = 1_000_000_000;
 
 /// Implementation of `sys_nanosleep`.
 ///
 /// # Arguments
 /// * `req` - Pointer to the requested sleep time. Must not be null.
 /// * `rem` - Optional pointer to store remaining time if the sleep is
-///   interrupted.
+///   interrupted by a signal, in which case `-EINTR` is returned.
 ///
 /// # Notes
-/// This is a kernel-space implementation and does not call the Linux syscall.
+/// The task sleeps in the timer queue and is woken by the timer interrupt, so
+/// the sleep is rounded up to the timer tick.
 pub fn sys_nanosleep(req: *const KernelTimespec, rem: *mut KernelTimespec) -> isize {
+	let req = UserPtr::new(req);
 	if req.is_null() {
 		return -EINVAL;
 	}
 
-	// Safely read the user-provided timespec
-	let ts = unsafe { &*req }; // stack-local copy
-
-	// Convert seconds and nanoseconds into total nanoseconds
-	let total_ns = ts.tv_sec as u64 * 1_000_000_000 + ts.tv_nsec as u64;
+	// Read the user-provided timespec through the app's page table
+	let ts = match req.read() {
+		Ok(ts) => ts,
+		Err(errno) => return errno,
+	};
+	if ts.tv_sec < 0 || !(0..NANO_PER_SEC as i64).contains(&ts.tv_nsec) {
+		return -EINVAL;
+	}
 
-	// Perform the sleep using busy-wait
-	sleep_ns(total_ns);
+	// Convert seconds and nanoseconds into `time` ticks, rounding up
+	let ticks = (ts.tv_sec as u64)
+		.saturating_mul(MTIME_FREQUENCY_HZ)
+		.saturating_add((ts.tv_nsec as u64 * MTIME_FREQUENCY_HZ).div_ceil(NANO_PER_SEC));
+	let deadline = get_time().saturating_add(ticks);
 
-	// Handle the remaining time if interrupted (not implemented yet)
-	if !rem.is_null() {
-		unimplemented!("Remaining time handling requires signal/interruption logic");
+	loop {
+		let now = get_time();
+		if now >= deadline {
+			return 0;
+		}
+		if current_interrupted() {
+			let left = deadline - now;
+			let rem = UserPtr::new(rem);
+			if !rem.is_null() {
+				let nsec = (left % MTIME_FREQUENCY_HZ) * NANO_PER_SEC / MTIME_FREQUENCY_HZ;
+				let ts = KernelTimespec::new((left / MTIME_FREQUENCY_HZ) as i64, nsec as i64);
+				if let Err(errno) = rem.write(ts) {
+					return errno;
+				}
+			}
+			return -EINTR;
+		}
+		sleep_current_and_run_next(deadline);
 	}
-
-	0
 }
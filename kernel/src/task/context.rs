@@ -1,8 +1,10 @@
+use crate::trap::trap_return;
+
 /// Task Context
 #[derive(Copy, Clone)]
 #[repr(C)]
 pub struct TaskContext {
-	/// return address ( e.g. __restore ) of __switch ASM function
+	/// return address ( e.g. trap_return ) of __switch ASM function
 	ra: usize,
 	/// kernel stack pointer of app
 	sp: usize,
@@ -14,11 +16,8 @@ impl TaskContext {
 	/// init task context
 	pub fn zero_init() -> Self { Self { ra: 0, sp: 0, s: [0; 12] } }
 
-	/// set task context {__restore ASM function, kernel stack, s_0..12 }
-	pub fn goto_restore(kstack_ptr: usize) -> Self {
-		unsafe extern "C" {
-			unsafe fn __restore();
-		}
-		Self { ra: __restore as *const () as usize, sp: kstack_ptr, s: [0; 12] }
+	/// set task context {trap_return, kernel stack, s_0..12 }
+	pub fn goto_trap_return(kstack_ptr: usize) -> Self {
+		Self { ra: trap_return as *const () as usize, sp: kstack_ptr, s: [0; 12] }
 	}
 }
@@ -0,0 +1,65 @@
+//! Implementation of [`TaskManager`]
+
+use alloc::{collections::BTreeMap, sync::Arc};
+
+use lazy_static::lazy_static;
+
+use crate::{sync::UPSafeCell, task::{scheduler::{Policy, Scheduler}, task::TaskControlBlock}};
+
+/// The `Ready` tasks, scheduled by the [`Policy`] the kernel is built with.
+pub struct TaskManager {
+	scheduler: Policy,
+}
+
+impl TaskManager {
+	pub fn new() -> Self { Self { scheduler: Policy::new() } }
+
+	pub fn add(&mut self, task: Arc<TaskControlBlock>) { self.scheduler.add(task); }
+
+	pub fn remove(&mut self, task: &Arc<TaskControlBlock>) -> bool { self.scheduler.remove(task) }
+
+	pub fn fetch(&mut self) -> Option<Arc<TaskControlBlock>> { self.scheduler.pick_next() }
+
+	pub fn tick(&mut self, current: &Arc<TaskControlBlock>) -> bool { self.scheduler.on_tick(current) }
+}
+
+lazy_static! {
+	pub static ref TASK_MANAGER: UPSafeCell<TaskManager> = unsafe { UPSafeCell::new(TaskManager::new()) };
+	/// Every task that has not exited, by pid.
+	pub static ref PID2TCB: UPSafeCell<BTreeMap<usize, Arc<TaskControlBlock>>> =
+		unsafe { UPSafeCell::new(BTreeMap::new()) };
+}
+
+/// Make `task` ready to run.
+pub fn add_task(task: Arc<TaskControlBlock>) { TASK_MANAGER.exclusive_access().add(task); }
+
+/// Take the next task to run.
+pub fn fetch_task() -> Option<Arc<TaskControlBlock>> { TASK_MANAGER.exclusive_access().fetch() }
+
+/// Account a timer tick to `current`, returns whether it should give up the
+/// CPU.
+pub fn tick_task(current: &Arc<TaskControlBlock>) -> bool { TASK_MANAGER.exclusive_access().tick(current) }
+
+/// Set the priority of `task`, a ready task is queued again so that the
+/// policy sees the new priority.
+pub fn set_task_priority(task: &Arc<TaskControlBlock>, priority: usize) {
+	let mut manager = TASK_MANAGER.exclusive_access();
+	let queued = manager.remove(task);
+	task.inner_exclusive_access().priority = priority;
+	if queued {
+		manager.add(task.clone());
+	}
+}
+
+/// Get the task `pid`, `None` if there is none or it has exited.
+pub fn pid2task(pid: usize) -> Option<Arc<TaskControlBlock>> { PID2TCB.exclusive_access().get(&pid).cloned() }
+
+pub fn insert_into_pid2task(pid: usize, task: Arc<TaskControlBlock>) {
+	PID2TCB.exclusive_access().insert(pid, task);
+}
+
+pub fn remove_from_pid2task(pid: usize) {
+	if PID2TCB.exclusive_access().remove(&pid).is_none() {
+		panic!("cannot find pid {} in pid2task!", pid);
+	}
+}
@@ -1,197 +1,235 @@
-use crate::{config::MAX_APP_NUM, println, sbi::shutdown, task::{context::TaskContext, global::UPSafeCell, switch::__switch}};
+//! Task management: processes created by `fork`, replaced by `execve` and
+//! reaped by their parent through `wait4`, all descending from [`INITPROC`].
+//!
+//! The [`processor`] runs the current task and the [`manager`] keeps the
+//! ready tasks for the [`scheduler`] policy, the functions here implement the
+//! task state transitions on top of them.
+
+use alloc::sync::Arc;
+
+use lazy_static::lazy_static;
+pub use manager::{add_task, pid2task, set_task_priority};
+pub use processor::{current_task, current_trap_cx, current_user_token, run_tasks};
+pub use task::TaskControlBlock;
+pub use timer::wake_expired_timers;
+pub use usage::{to_clock_ticks, to_time_val};
+pub use wait_queue::WaitQueue;
+
+use crate::{config::{MTIME_FREQUENCY_HZ, TICKS_PER_SEC}, loader::get_app_data_by_name, memory::{MapPerm, MemorySet, VirtAddr}, sbi::{get_time, shutdown}, task::{context::TaskContext, manager::{insert_into_pid2task, remove_from_pid2task}, processor::{schedule, take_current_task}, task::{ExitReason, TaskStatus}}};
 
 mod context;
+mod manager;
+mod pid;
+mod processor;
+mod scheduler;
+mod signal;
+mod summary;
 mod switch;
+#[allow(clippy::module_inception)]
+mod task;
+mod timer;
+mod usage;
+mod wait_queue;
+
+lazy_static! {
+	/// The first process, it starts the shell and adopts the orphans.
+	pub static ref INITPROC: Arc<TaskControlBlock> =
+		Arc::new(TaskControlBlock::new("initproc", get_app_data_by_name("initproc").expect("initproc is missing")));
+}
 
-#[derive(Copy, Clone)]
-pub struct TaskControlBlock {
-	pub task_status: TaskStatus,
-	pub task_cx:     TaskContext,
+/// Make [`INITPROC`] ready to run.
+pub fn add_initproc() {
+	insert_into_pid2task(INITPROC.getpid(), INITPROC.clone());
+	add_task(INITPROC.clone());
 }
 
-#[derive(Copy, Clone, PartialEq)]
-pub enum TaskStatus {
-	UnInit,
-	Ready,
-	Running,
-	Exited,
+/// suspend current task, then run next task
+pub fn suspend_current_and_run_next() { suspend_current(true); }
+
+/// suspend current task preempted by the timer, then run next task
+pub fn preempt_current_and_run_next() { suspend_current(false); }
+
+/// suspend current task, which gave up the CPU itself if `voluntary`, then
+/// run next task
+fn suspend_current(voluntary: bool) {
+	let task = take_current_task().unwrap();
+	let mut task_inner = task.inner_exclusive_access();
+	let task_cx_ptr = &mut task_inner.task_cx as *mut TaskContext;
+	task_inner.task_status = TaskStatus::Ready;
+	task_inner.usage.switch_out(get_time(), voluntary);
+	drop(task_inner);
+	add_task(task);
+	schedule(task_cx_ptr);
 }
 
-mod global {
-	use core::cell::{RefCell, RefMut};
+/// Take current task off the processor in `status`, without putting it back
+/// on the ready queue, then run next task.
+fn park_current(status: TaskStatus, voluntary: bool) {
+	let task = take_current_task().unwrap();
+	let mut task_inner = task.inner_exclusive_access();
+	let task_cx_ptr = &mut task_inner.task_cx as *mut TaskContext;
+	task_inner.task_status = status;
+	task_inner.usage.switch_out(get_time(), voluntary);
+	drop(task_inner);
+	// the pid table keeps the task alive while it is off the ready queue
+	drop(task);
+	schedule(task_cx_ptr);
+}
+
+/// Put current task to sleep until something calls [`wake_task`] on it, then
+/// run next task.
+///
+/// Whatever the task waits for has to keep a way to wake it, a signal may
+/// wake it earlier.
+pub fn block_current_and_run_next() { park_current(TaskStatus::Sleeping, true); }
+
+/// Stop current task until it is continued or killed, see [`wake_task`], then
+/// run next task.
+fn stop_current_and_run_next() { park_current(TaskStatus::Stopped, false); }
 
-	use lazy_static::lazy_static;
+/// Put current task to sleep until `time` reaches `deadline`, then run next
+/// task.
+///
+/// The task may be woken earlier by a signal, see [`wake_task`].
+pub fn sleep_current_and_run_next(deadline: u64) {
+	timer::add_timer(deadline, &current_task().unwrap());
+	block_current_and_run_next();
+}
 
-	use crate::{config::MAX_APP_NUM, loader::{get_num_app, init_app_cx}, task::{TaskControlBlock, TaskManager, TaskManagerInner, TaskStatus, context::TaskContext}};
+/// Put current task to sleep for a timer tick, then run next task.
+///
+/// Blocking syscalls without a wait queue poll this way for what they wait
+/// for, so that the processor can idle while nothing else is ready.
+pub fn nap_current_and_run_next() {
+	sleep_current_and_run_next(get_time() + MTIME_FREQUENCY_HZ / TICKS_PER_SEC);
+}
 
-	/// Wrap a static data structure inside it so that we are able to access it
-	/// without any `unsafe`.
-	///
-	/// We should only use it in Uniprocessor.
-	///
-	/// In order to get mutable reference of inner data, call
-	/// [`UpSafeCell::exclusive_access()`]
-	pub(super) struct UPSafeCell<T> {
-		inner: RefCell<T>,
+/// Make `task` ready to run again if it is sleeping, or if it is stopped and
+/// a signal continued or killed it.
+pub fn wake_task(task: &Arc<TaskControlBlock>) {
+	let mut inner = task.inner_exclusive_access();
+	let wake = match inner.task_status {
+		TaskStatus::Sleeping => true,
+		TaskStatus::Stopped => !inner.signals.stays_stopped(),
+		_ => false,
+	};
+	if wake {
+		inner.task_status = TaskStatus::Ready;
+		drop(inner);
+		add_task(task.clone());
 	}
+}
 
-	unsafe impl<T> Sync for UPSafeCell<T> {}
+/// Account a timer tick to the current task, returns whether the scheduling
+/// policy wants it to give up the CPU.
+pub fn tick_current_task() -> bool { manager::tick_task(&current_task().unwrap()) }
 
-	impl<T> UPSafeCell<T> {
-		/// User is responsible to guarantee that inner struct is only used in
-		/// Uniprocessor.
-		unsafe fn new(value: T) -> Self { Self { inner: RefCell::new(value) } }
+/// charge current task for the time in user mode, it just trapped into the
+/// kernel
+pub fn current_enter_kernel() {
+	current_task().unwrap().inner_exclusive_access().usage.enter_kernel(get_time());
+}
 
-		/// Exclusive access inner data in [`UpSafeCell`]. Panic if the data has
-		/// been borrowed.
-		pub fn exclusive_access(&self) -> RefMut<'_, T> { self.inner.borrow_mut() }
-	}
+/// charge current task for the time in the kernel, it returns to user mode
+pub fn current_enter_user() { current_task().unwrap().inner_exclusive_access().usage.enter_user(get_time()); }
 
-	// `lazy_static!` help us initialize a global variable at the first time it is
-	// used.
-	#[rustfmt::skip]
-	lazy_static! {
-        pub(super) static ref TASK_MANAGER: TaskManager = {
-            let num_app = get_num_app();
-            let mut tasks = [TaskControlBlock {
-                task_cx: TaskContext::zero_init(),
-                task_status: TaskStatus::UnInit,
-            }; MAX_APP_NUM];
-            for (i, task) in tasks.iter_mut().enumerate() {
-                task.task_cx = TaskContext::goto_restore(init_app_cx(i));
-                task.task_status = TaskStatus::Ready;
-            }
-            TaskManager {
-                num_app,
-                inner: unsafe {
-                    UPSafeCell::new(TaskManagerInner {
-                        tasks,
-                        current_task: 0,
-                    })
-                },
-            }
-        };
+/// count a syscall `syscall_id` of current task for `task_info`
+pub fn record_current_syscall(syscall_id: usize) {
+	let task = current_task().unwrap();
+	if let Some(count) = task.inner_exclusive_access().syscall_times.get_mut(syscall_id) {
+		*count += 1;
 	}
 }
 
-use global::TASK_MANAGER;
+/// exit current task with `exit_code`, then run next task
+pub fn exit_current_and_run_next(exit_code: i32) { exit_current(ExitReason::Exited(exit_code)); }
 
-/// The task manager, where all the tasks are managed.
+/// raise `sig` for a fault of current task, see [`SignalState::force`]
 ///
-/// Functions implemented on `TaskManager` deals with all task state transitions
-/// and task context switching. For convenience, you can find wrappers around it
-/// in the module level.
+/// [`SignalState::force`]: signal::SignalState::force
+pub fn force_current_signal(sig: usize) {
+	current_task().unwrap().inner_exclusive_access().signals.force(sig);
+}
+
+/// whether a signal should interrupt the blocking syscall of current task
+pub fn current_interrupted() -> bool { current_task().unwrap().inner_exclusive_access().signals.interrupts() }
+
+/// handle the signals of current task before it returns to user mode
 ///
-/// Most of `TaskManager` are hidden behind the field `inner`, to defer
-/// borrowing checks to runtime. You can see examples on how to use `inner` in
-/// existing functions on `TaskManager`.
-pub struct TaskManager {
-	/// total number of tasks
-	num_app: usize,
-	/// use inner value to get mutable access
-	inner:   UPSafeCell<TaskManagerInner>,
-}
-
-/// Inner of Task Manager
-pub struct TaskManagerInner {
-	/// task list
-	tasks:        [TaskControlBlock; MAX_APP_NUM],
-	/// id of current `Running` task
-	current_task: usize,
-}
-
-impl TaskManager {
-	/// Run the first task in task list.
-	///
-	/// Generally, the first task in task list is an idle task (we call it zero
-	/// process later). But in ch3, we load apps statically, so the first task is
-	/// a real app.
-	fn run_first_task(&self) -> ! {
-		let mut inner = self.inner.exclusive_access();
-		let task0 = &mut inner.tasks[0];
-		task0.task_status = TaskStatus::Running;
-		let next_task_cx_ptr = &task0.task_cx as *const TaskContext;
+/// A stopped task is taken off the ready queue until it is continued, a
+/// terminated task exits and never returns from here.
+pub fn handle_current_signals() {
+	loop {
+		let task = current_task().unwrap();
+		let mut inner = task.inner_exclusive_access();
+		let trap_cx = inner.get_trap_cx();
+		inner.signals.handle_pending(trap_cx);
+		let (frozen, killed) = (inner.signals.frozen(), inner.signals.killed());
 		drop(inner);
-		let mut _unused = TaskContext::zero_init();
-		// before this, we should drop local variables that must be dropped manually
-		unsafe {
-			__switch(&mut _unused as *mut TaskContext, next_task_cx_ptr);
+		drop(task);
+		if let Some(sig) = killed {
+			exit_current(ExitReason::Killed(sig));
 		}
-		panic!("unreachable in run_first_task!");
-	}
-
-	/// Change the status of current `Running` task into `Ready`.
-	fn mark_current_suspended(&self) {
-		let mut inner = self.inner.exclusive_access();
-		let current = inner.current_task;
-		inner.tasks[current].task_status = TaskStatus::Ready;
-	}
-
-	/// Change the status of current `Running` task into `Exited`.
-	fn mark_current_exited(&self) {
-		let mut inner = self.inner.exclusive_access();
-		let current = inner.current_task;
-		inner.tasks[current].task_status = TaskStatus::Exited;
-	}
-
-	/// Switch current `Running` task to the task we have found,
-	/// or there is no `Ready` task and we can exit with all applications
-	/// completed
-	fn run_next_task(&self) {
-		if let Some(next) = self.find_next_task() {
-			let (current_task_cx_ptr, next_task_cx_ptr) = {
-				let mut inner = self.inner.exclusive_access();
-				let current = inner.current_task;
-				inner.tasks[next].task_status = TaskStatus::Running;
-				inner.current_task = next;
-				(
-					&mut inner.tasks[current].task_cx as *mut TaskContext,
-					&inner.tasks[next].task_cx as *const TaskContext,
-				)
-			};
-			// before this, we should drop local variables that must be dropped manually
-			unsafe {
-				__switch(current_task_cx_ptr, next_task_cx_ptr);
-			}
-			// go back to user mode
-		} else {
-			println!("All applications completed!");
-			shutdown(false);
+		if !frozen {
+			break;
 		}
+		stop_current_and_run_next();
 	}
+}
 
-	/// Find next task to run and return app id.
-	///
-	/// In this case, we only return the first `Ready` task in task list.
-	fn find_next_task(&self) -> Option<usize> {
-		let inner = self.inner.exclusive_access();
-		let current = inner.current_task;
-		(current + 1..current + self.num_app + 1)
-			.map(|id| id % self.num_app)
-			.find(|id| inner.tasks[*id].task_status == TaskStatus::Ready)
+/// End current task for `reason` and run next task.
+///
+/// The task stays a zombie until its parent reaps it, its children are
+/// adopted by [`INITPROC`]. Once [`INITPROC`] exits the system prints the
+/// summary of every task and shuts down, reporting a failure if any task
+/// failed.
+fn exit_current(reason: ExitReason) {
+	let task = current_task().unwrap();
+	remove_from_pid2task(task.getpid());
+	let mut inner = task.inner_exclusive_access();
+	summary::record_exit(task.getpid(), inner.name.clone(), reason);
+	if Arc::ptr_eq(&task, &INITPROC) {
+		drop(inner);
+		shutdown_with_summary();
 	}
+	inner.task_status = TaskStatus::Zombie;
+	inner.usage.charge_kernel(get_time());
+	inner.exit_reason = reason;
+	let mut initproc_inner = INITPROC.inner_exclusive_access();
+	for child in inner.children.drain(..) {
+		child.inner_exclusive_access().parent = Some(Arc::downgrade(&INITPROC));
+		initproc_inner.children.push(child);
+	}
+	drop(initproc_inner);
+	inner.memory_set.recycle_data_pages();
+	drop(inner);
+	// the processor keeps the task until the idle control flow has left its
+	// kernel stack
+	drop(task);
+	let mut _unused = TaskContext::zero_init();
+	schedule(&mut _unused as *mut _);
 }
 
-/// run first task
-pub fn run_first_task() { TASK_MANAGER.run_first_task(); }
-
-/// rust next task
-fn run_next_task() { TASK_MANAGER.run_next_task(); }
-
-/// suspend current task
-fn mark_current_suspended() { TASK_MANAGER.mark_current_suspended(); }
+/// Print the summary of every task and shut down, reporting a failure if any
+/// task failed.
+fn shutdown_with_summary() -> ! {
+	let failed = summary::print_summary();
+	shutdown(failed);
+}
 
-/// exit current task
-fn mark_current_exited() { TASK_MANAGER.mark_current_exited(); }
+/// map a frame for the page fault of current task at `va` caused by an
+/// `access`, returns `false` if the access is illegal or no frame is left
+pub fn handle_current_page_fault(va: VirtAddr, access: MapPerm) -> bool {
+	current_task().unwrap().inner_exclusive_access().memory_set.handle_page_fault(va, access)
+}
 
-/// suspend current task, then run next task
-pub fn suspend_current_and_run_next() {
-	mark_current_suspended();
-	run_next_task();
+/// move the program break of current task to `brk`, returns the program
+/// break afterwards
+pub fn change_program_brk(brk: usize) -> usize {
+	current_task().unwrap().inner_exclusive_access().change_program_brk(brk)
 }
 
-/// exit current task,  then run next task
-pub fn exit_current_and_run_next() {
-	mark_current_exited();
-	run_next_task();
+/// run `f` on the address space of current task
+pub fn with_current_memory_set<T>(f: impl FnOnce(&mut MemorySet) -> T) -> T {
+	f(&mut current_task().unwrap().inner_exclusive_access().memory_set)
 }
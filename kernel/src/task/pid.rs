@@ -0,0 +1,88 @@
+//! Process identifiers and the kernel stacks placed by them.
+
+use alloc::vec::Vec;
+
+use lazy_static::lazy_static;
+
+use crate::{config::{KERNEL_STACK_SIZE, PAGE_SIZE, TRAMPOLINE}, memory::{KERNEL_SPACE, MapPerm, VirtAddr}, sync::UPSafeCell};
+
+/// Hands out pids from `current` upwards, and reuses pids given back in
+/// `recycled` first.
+pub struct PidAllocator {
+	/// Smallest pid never handed out.
+	current:  usize,
+	/// Pids that were handed out and then given back.
+	recycled: Vec<usize>,
+}
+
+impl PidAllocator {
+	pub fn new() -> Self { Self { current: 0, recycled: Vec::new() } }
+
+	pub fn alloc(&mut self) -> PidHandle {
+		if let Some(pid) = self.recycled.pop() {
+			PidHandle(pid)
+		} else {
+			self.current += 1;
+			PidHandle(self.current - 1)
+		}
+	}
+
+	pub fn dealloc(&mut self, pid: usize) {
+		if pid >= self.current || self.recycled.contains(&pid) {
+			panic!("pid {} has not been allocated!", pid);
+		}
+		self.recycled.push(pid);
+	}
+}
+
+lazy_static! {
+	static ref PID_ALLOCATOR: UPSafeCell<PidAllocator> = unsafe { UPSafeCell::new(PidAllocator::new()) };
+}
+
+/// RAII handle of a pid, the pid goes back to the allocator when it is
+/// dropped.
+pub struct PidHandle(pub usize);
+
+impl Drop for PidHandle {
+	fn drop(&mut self) { PID_ALLOCATOR.exclusive_access().dealloc(self.0); }
+}
+
+/// Allocate a pid.
+pub fn pid_alloc() -> PidHandle { PID_ALLOCATOR.exclusive_access().alloc() }
+
+/// Bottom and top of the kernel stack of `pid` in the kernel space.
+///
+/// The stacks sit right below the trampoline, each one has an unmapped
+/// guard page below it so that an overflow faults instead of running into
+/// the next stack.
+fn kernel_stack_position(pid: usize) -> (usize, usize) {
+	let top = TRAMPOLINE - pid * (KERNEL_STACK_SIZE + PAGE_SIZE);
+	let bottom = top - KERNEL_STACK_SIZE;
+	(bottom, top)
+}
+
+/// Kernel stack of a process, mapped in the kernel space while the process
+/// exists.
+pub struct KernelStack {
+	pid: usize,
+}
+
+impl KernelStack {
+	/// Map the kernel stack of the process `pid_handle`.
+	pub fn new(pid_handle: &PidHandle) -> Self {
+		let pid = pid_handle.0;
+		let (bottom, top) = kernel_stack_position(pid);
+		KERNEL_SPACE.exclusive_access().insert_framed_area(bottom.into(), top.into(), MapPerm::R | MapPerm::W);
+		Self { pid }
+	}
+
+	/// Top of the stack, usable as `sp` while the kernel space is active.
+	pub fn get_sp(&self) -> usize { kernel_stack_position(self.pid).1 }
+}
+
+impl Drop for KernelStack {
+	fn drop(&mut self) {
+		let (bottom, _) = kernel_stack_position(self.pid);
+		KERNEL_SPACE.exclusive_access().remove_area_with_start_vpn(VirtAddr::from(bottom).into());
+	}
+}
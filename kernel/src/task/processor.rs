@@ -0,0 +1,103 @@
+//! Implementation of [`Processor`] and the idle control flow.
+
+use alloc::sync::Arc;
+
+use lazy_static::lazy_static;
+use riscv::{asm::wfi, register::sip};
+
+use crate::{console, sbi::{get_time, set_next_trigger}, sync::UPSafeCell, task::{context::TaskContext, manager::fetch_task, switch::__switch, task::{TaskControlBlock, TaskStatus}, wake_expired_timers}, trap::context::TrapContext};
+
+/// The state of the processor.
+pub struct Processor {
+	/// task running on the processor
+	current:      Option<Arc<TaskControlBlock>>,
+	/// context of the idle control flow in [`run_tasks`]
+	idle_task_cx: TaskContext,
+}
+
+impl Processor {
+	pub fn new() -> Self { Self { current: None, idle_task_cx: TaskContext::zero_init() } }
+
+	fn get_idle_task_cx_ptr(&mut self) -> *mut TaskContext { &mut self.idle_task_cx as *mut _ }
+
+	/// Take the current task, leaving `None` behind.
+	pub fn take_current(&mut self) -> Option<Arc<TaskControlBlock>> { self.current.take() }
+
+	/// Get a clone of the current task.
+	pub fn current(&self) -> Option<Arc<TaskControlBlock>> { self.current.clone() }
+}
+
+lazy_static! {
+	pub static ref PROCESSOR: UPSafeCell<Processor> = unsafe { UPSafeCell::new(Processor::new()) };
+}
+
+/// The idle control flow: pick a ready task and switch to it, forever.
+///
+/// While no task is ready, for instance when they are all sleeping, the
+/// processor waits for an interrupt. `initproc` never leaves, the system
+/// shuts down when it exits.
+pub fn run_tasks() -> ! {
+	loop {
+		let mut processor = PROCESSOR.exclusive_access();
+		// an exited task is dropped here, after we left its kernel stack
+		processor.take_current();
+		if let Some(task) = fetch_task() {
+			let idle_task_cx_ptr = processor.get_idle_task_cx_ptr();
+			let mut task_inner = task.inner_exclusive_access();
+			let next_task_cx_ptr = &task_inner.task_cx as *const TaskContext;
+			task_inner.task_status = TaskStatus::Running;
+			let now = get_time();
+			task_inner.first_run.get_or_insert(now);
+			task_inner.usage.switch_in(now);
+			drop(task_inner);
+			processor.current = Some(task);
+			drop(processor);
+			// before this, we should drop local variables that must be dropped
+			// manually
+			unsafe {
+				__switch(idle_task_cx_ptr, next_task_cx_ptr);
+			}
+		} else {
+			drop(processor);
+			wait_for_interrupt();
+		}
+	}
+}
+
+/// Wait in `wfi` for an interrupt, and handle a timer interrupt by waking the
+/// tasks whose sleep is over or whose console input arrived.
+///
+/// `sstatus.SIE` stays clear in the kernel, so the interrupt is not taken as
+/// a trap, but `wfi` still returns once an interrupt enabled in `sie` is
+/// pending.
+fn wait_for_interrupt() {
+	wfi();
+	if sip::read().stimer() {
+		set_next_trigger();
+		wake_expired_timers();
+		console::poll_input();
+	}
+}
+
+/// Take the current task, leaving `None` behind.
+pub fn take_current_task() -> Option<Arc<TaskControlBlock>> { PROCESSOR.exclusive_access().take_current() }
+
+/// Get a clone of the current task.
+pub fn current_task() -> Option<Arc<TaskControlBlock>> { PROCESSOR.exclusive_access().current() }
+
+/// Get the address space token of the current task.
+pub fn current_user_token() -> usize { current_task().unwrap().inner_exclusive_access().get_user_token() }
+
+/// Get the `TrapContext` of the current task.
+pub fn current_trap_cx() -> &'static mut TrapContext {
+	current_task().unwrap().inner_exclusive_access().get_trap_cx()
+}
+
+/// Switch from the current task, whose context is saved in
+/// `switched_task_cx_ptr`, back to the idle control flow.
+pub fn schedule(switched_task_cx_ptr: *mut TaskContext) {
+	let idle_task_cx_ptr = PROCESSOR.exclusive_access().get_idle_task_cx_ptr();
+	unsafe {
+		__switch(switched_task_cx_ptr, idle_task_cx_ptr);
+	}
+}
@@ -0,0 +1,69 @@
+//! A completely-fair-scheduler-like policy: each task accumulates virtual
+//! runtime, its CPU time scaled down by its priority, and the task with the
+//! least virtual runtime runs next.
+
+use alloc::{collections::BTreeMap, sync::Arc};
+
+use crate::{config::{DEFAULT_PRIORITY, MICRO_PER_SEC, TICKS_PER_SEC}, task::{TaskControlBlock, scheduler::Scheduler}};
+
+/// The `Ready` tasks ordered by virtual runtime, then by the order they were
+/// added.
+pub struct Cfs {
+	ready:        BTreeMap<(u64, u64), Arc<TaskControlBlock>>,
+	/// number of tasks added so far, to break ties
+	seq:          u64,
+	/// smallest virtual runtime seen among the picked tasks, it never goes
+	/// back
+	min_vruntime: u64,
+}
+
+/// CFS state of a task.
+#[derive(Clone, Default)]
+pub struct CfsEntity {
+	/// virtual runtime in microseconds, a task with the default priority
+	/// gets one microsecond per microsecond of CPU time
+	vruntime: u64,
+	/// key of the task in the ready tasks while it is there
+	key:      Option<(u64, u64)>,
+}
+
+impl Cfs {
+	pub fn new() -> Self { Self { ready: BTreeMap::new(), seq: 0, min_vruntime: 0 } }
+}
+
+impl Scheduler for Cfs {
+	type Entity = CfsEntity;
+
+	/// A task that was away, new or waiting, is not allowed to have fallen
+	/// behind `min_vruntime`, it would hold the CPU until it caught up.
+	fn add(&mut self, task: Arc<TaskControlBlock>) {
+		let mut inner = task.inner_exclusive_access();
+		inner.sched.vruntime = inner.sched.vruntime.max(self.min_vruntime);
+		let key = (inner.sched.vruntime, self.seq);
+		inner.sched.key = Some(key);
+		drop(inner);
+		self.seq += 1;
+		self.ready.insert(key, task);
+	}
+
+	fn remove(&mut self, task: &Arc<TaskControlBlock>) -> bool {
+		let key = task.inner_exclusive_access().sched.key.take();
+		key.is_some_and(|key| self.ready.remove(&key).is_some())
+	}
+
+	fn pick_next(&mut self) -> Option<Arc<TaskControlBlock>> {
+		let ((vruntime, _), task) = self.ready.pop_first()?;
+		self.min_vruntime = self.min_vruntime.max(vruntime);
+		task.inner_exclusive_access().sched.key = None;
+		Some(task)
+	}
+
+	/// Charge the tick to `current`, which is preempted once a ready task has
+	/// less virtual runtime.
+	fn on_tick(&mut self, current: &Arc<TaskControlBlock>) -> bool {
+		let mut inner = current.inner_exclusive_access();
+		let tick = MICRO_PER_SEC / TICKS_PER_SEC;
+		inner.sched.vruntime += tick * DEFAULT_PRIORITY as u64 / inner.priority as u64;
+		self.ready.first_key_value().is_some_and(|((vruntime, _), _)| *vruntime < inner.sched.vruntime)
+	}
+}
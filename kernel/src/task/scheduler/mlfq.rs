@@ -0,0 +1,90 @@
+//! Multi-level feedback queue: a task starts in the top level, and moves a
+//! level down whenever it uses up the time slice of its level, which doubles
+//! from one level to the next. Lower levels only run when the levels above
+//! are empty, and every task moves back to the top now and then so that none
+//! of them starves.
+
+use alloc::{collections::VecDeque, sync::Arc};
+
+use crate::task::{TaskControlBlock, scheduler::Scheduler};
+
+/// Number of levels.
+const LEVELS: usize = 3;
+/// Every task goes back to the top level after this many ticks.
+const BOOST_TICKS: usize = 100;
+
+/// Time slice of `level`, in ticks.
+const fn time_slice(level: usize) -> usize { 1 << level }
+
+/// One FIFO queue of `Ready` tasks per level, the top level first.
+pub struct Mlfq {
+	levels:         [VecDeque<Arc<TaskControlBlock>>; LEVELS],
+	/// ticks until the next boost
+	ticks_to_boost: usize,
+}
+
+/// MLFQ state of a task.
+#[derive(Clone, Default)]
+pub struct MlfqEntity {
+	level:      usize,
+	/// ticks of the time slice the task has used
+	used_ticks: usize,
+}
+
+impl Mlfq {
+	pub fn new() -> Self { Self { levels: Default::default(), ticks_to_boost: BOOST_TICKS } }
+
+	/// Move every task back to the top level with a fresh time slice.
+	fn boost(&mut self, current: &Arc<TaskControlBlock>) {
+		for level in 1..LEVELS {
+			while let Some(task) = self.levels[level].pop_front() {
+				self.levels[0].push_back(task);
+			}
+		}
+		for task in self.levels[0].iter().chain([current]) {
+			task.inner_exclusive_access().sched = MlfqEntity::default();
+		}
+	}
+}
+
+impl Scheduler for Mlfq {
+	type Entity = MlfqEntity;
+
+	fn add(&mut self, task: Arc<TaskControlBlock>) {
+		let level = task.inner_exclusive_access().sched.level;
+		self.levels[level].push_back(task);
+	}
+
+	fn remove(&mut self, task: &Arc<TaskControlBlock>) -> bool {
+		let level = task.inner_exclusive_access().sched.level;
+		let queue = &mut self.levels[level];
+		let len = queue.len();
+		queue.retain(|t| !Arc::ptr_eq(t, task));
+		queue.len() != len
+	}
+
+	fn pick_next(&mut self) -> Option<Arc<TaskControlBlock>> {
+		self.levels.iter_mut().find_map(|queue| queue.pop_front())
+	}
+
+	/// Charge the tick to the time slice of `current`, which is preempted once
+	/// the slice is used up, moving it a level down, or when a task is ready
+	/// in a level above.
+	fn on_tick(&mut self, current: &Arc<TaskControlBlock>) -> bool {
+		self.ticks_to_boost -= 1;
+		if self.ticks_to_boost == 0 {
+			self.ticks_to_boost = BOOST_TICKS;
+			self.boost(current);
+			return true;
+		}
+		let mut inner = current.inner_exclusive_access();
+		let entity = &mut inner.sched;
+		entity.used_ticks += 1;
+		if entity.used_ticks >= time_slice(entity.level) {
+			entity.level = (entity.level + 1).min(LEVELS - 1);
+			entity.used_ticks = 0;
+			return true;
+		}
+		self.levels[..entity.level].iter().any(|queue| !queue.is_empty())
+	}
+}
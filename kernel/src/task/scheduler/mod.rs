@@ -0,0 +1,78 @@
+//! Scheduling policies, the one the kernel runs with is picked by a cargo
+//! feature of the kernel crate, exactly one of them has to be enabled:
+//!
+//! - `sched-rr`: round-robin, [`rr::RoundRobin`]
+//! - `sched-stride`: stride scheduling by priority, [`stride::Stride`], the
+//!   default
+//! - `sched-mlfq`: multi-level feedback queue, [`mlfq::Mlfq`]
+//! - `sched-cfs`: virtual runtime like Linux CFS, [`cfs::Cfs`]
+//!
+//! Build with `--no-default-features --features sched-<policy>` to pick one
+//! other than the default.
+
+use alloc::sync::Arc;
+
+use crate::task::TaskControlBlock;
+
+#[cfg(feature = "sched-cfs")]
+mod cfs;
+#[cfg(feature = "sched-mlfq")]
+mod mlfq;
+#[cfg(feature = "sched-rr")]
+mod rr;
+#[cfg(feature = "sched-stride")]
+mod stride;
+
+#[cfg(feature = "sched-cfs")]
+pub type Policy = cfs::Cfs;
+#[cfg(feature = "sched-mlfq")]
+pub type Policy = mlfq::Mlfq;
+#[cfg(feature = "sched-rr")]
+pub type Policy = rr::RoundRobin;
+#[cfg(feature = "sched-stride")]
+pub type Policy = stride::Stride;
+
+#[cfg(not(any(
+	feature = "sched-rr",
+	feature = "sched-stride",
+	feature = "sched-mlfq",
+	feature = "sched-cfs"
+)))]
+compile_error!("enable one of the features sched-rr, sched-stride, sched-mlfq and sched-cfs");
+#[cfg(any(
+	all(feature = "sched-rr", feature = "sched-stride"),
+	all(feature = "sched-rr", feature = "sched-mlfq"),
+	all(feature = "sched-rr", feature = "sched-cfs"),
+	all(feature = "sched-stride", feature = "sched-mlfq"),
+	all(feature = "sched-stride", feature = "sched-cfs"),
+	all(feature = "sched-mlfq", feature = "sched-cfs")
+))]
+compile_error!(
+	"the features sched-rr, sched-stride, sched-mlfq and sched-cfs exclude each other, build with \
+	 --no-default-features to pick one other than sched-stride"
+);
+
+/// Scheduling state the policy keeps in every task.
+pub type SchedEntity = <Policy as Scheduler>::Entity;
+
+/// A scheduling policy, it owns the `Ready` tasks and decides which one runs
+/// next.
+pub trait Scheduler {
+	/// Scheduling state kept in every task, a forked task starts with a copy
+	/// of its parent's.
+	type Entity: Clone + Default;
+
+	/// Make `task` ready to run.
+	fn add(&mut self, task: Arc<TaskControlBlock>);
+
+	/// Take `task` out of the ready tasks, returns `false` if it is not
+	/// there.
+	fn remove(&mut self, task: &Arc<TaskControlBlock>) -> bool;
+
+	/// Take the next task to run.
+	fn pick_next(&mut self) -> Option<Arc<TaskControlBlock>>;
+
+	/// Account a timer tick to `current`, the running task, returns whether
+	/// it should give up the CPU.
+	fn on_tick(&mut self, current: &Arc<TaskControlBlock>) -> bool;
+}
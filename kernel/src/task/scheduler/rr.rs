@@ -0,0 +1,34 @@
+//! Round-robin: the ready tasks run in turn, one tick each.
+
+use alloc::{collections::VecDeque, sync::Arc};
+
+use crate::task::{TaskControlBlock, scheduler::Scheduler};
+
+/// FIFO queue of the `Ready` tasks.
+pub struct RoundRobin {
+	ready_queue: VecDeque<Arc<TaskControlBlock>>,
+}
+
+/// Round-robin keeps no state in the tasks.
+#[derive(Clone, Default)]
+pub struct RoundRobinEntity;
+
+impl RoundRobin {
+	pub fn new() -> Self { Self { ready_queue: VecDeque::new() } }
+}
+
+impl Scheduler for RoundRobin {
+	type Entity = RoundRobinEntity;
+
+	fn add(&mut self, task: Arc<TaskControlBlock>) { self.ready_queue.push_back(task); }
+
+	fn remove(&mut self, task: &Arc<TaskControlBlock>) -> bool {
+		let len = self.ready_queue.len();
+		self.ready_queue.retain(|t| !Arc::ptr_eq(t, task));
+		self.ready_queue.len() != len
+	}
+
+	fn pick_next(&mut self) -> Option<Arc<TaskControlBlock>> { self.ready_queue.pop_front() }
+
+	fn on_tick(&mut self, _current: &Arc<TaskControlBlock>) -> bool { true }
+}
@@ -0,0 +1,67 @@
+//! Stride scheduling: the task with the smallest pass runs next and its pass
+//! advances by its stride, `BIG_STRIDE / priority`, so each task gets a
+//! share of the CPU proportional to its priority.
+
+use alloc::{collections::VecDeque, sync::Arc};
+
+use crate::task::{TaskControlBlock, scheduler::Scheduler};
+
+/// Stride of a task whose priority is 1.
+const BIG_STRIDE: u64 = 1 << 20;
+
+/// The `Ready` tasks, in the order they were added.
+pub struct Stride {
+	ready_queue: VecDeque<Arc<TaskControlBlock>>,
+	/// pass of the task taken last, the smallest pass of any task then
+	min_pass:    u64,
+}
+
+/// Stride scheduling state of a task.
+#[derive(Clone, Default)]
+pub struct StrideEntity {
+	pass: u64,
+}
+
+impl Stride {
+	pub fn new() -> Self { Self { ready_queue: VecDeque::new(), min_pass: 0 } }
+}
+
+impl Scheduler for Stride {
+	type Entity = StrideEntity;
+
+	/// Add `task`, raising its pass to the smallest pass so far, a task that
+	/// was new or asleep would otherwise hog the CPU until it caught up.
+	fn add(&mut self, task: Arc<TaskControlBlock>) {
+		let mut inner = task.inner_exclusive_access();
+		if (inner.sched.pass.wrapping_sub(self.min_pass) as i64) < 0 {
+			inner.sched.pass = self.min_pass;
+		}
+		drop(inner);
+		self.ready_queue.push_back(task);
+	}
+
+	fn remove(&mut self, task: &Arc<TaskControlBlock>) -> bool {
+		let len = self.ready_queue.len();
+		self.ready_queue.retain(|t| !Arc::ptr_eq(t, task));
+		self.ready_queue.len() != len
+	}
+
+	/// Take the task with the smallest pass and advance its pass, tasks with
+	/// the same pass are taken in the order they were added.
+	fn pick_next(&mut self) -> Option<Arc<TaskControlBlock>> {
+		// passes wrap around, they are compared by their distance which stays
+		// below `BIG_STRIDE` as long as every task keeps being scheduled
+		let idx = (0..self.ready_queue.len()).reduce(|min, idx| {
+			let pass = |idx: usize| self.ready_queue[idx].inner_exclusive_access().sched.pass;
+			if (pass(idx).wrapping_sub(pass(min)) as i64) < 0 { idx } else { min }
+		})?;
+		let task = self.ready_queue.remove(idx).unwrap();
+		let mut inner = task.inner_exclusive_access();
+		self.min_pass = inner.sched.pass;
+		inner.sched.pass = inner.sched.pass.wrapping_add(BIG_STRIDE / inner.priority as u64);
+		drop(inner);
+		Some(task)
+	}
+
+	fn on_tick(&mut self, _current: &Arc<TaskControlBlock>) -> bool { true }
+}
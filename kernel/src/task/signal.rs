@@ -0,0 +1,219 @@
+//! Signals of a task: the pending and blocked sets, the actions, and their
+//! delivery when the task returns to user mode.
+
+use config::signal::*;
+
+use crate::trap::context::TrapContext;
+
+/// Signals that can neither be caught, ignored nor blocked.
+const UNCATCHABLE: SigSet = sigmask(SIGKILL) | sigmask(SIGSTOP);
+
+/// Signals that stop the task until `SIGCONT`.
+const STOP_SIGNALS: SigSet = sigmask(SIGSTOP) | sigmask(SIGTSTP) | sigmask(SIGTTIN) | sigmask(SIGTTOU);
+
+/// What a signal left to `SIG_DFL` does.
+#[derive(PartialEq)]
+enum DefaultAction {
+	Terminate,
+	Ignore,
+	Stop,
+	Continue,
+}
+
+impl DefaultAction {
+	fn of(sig: usize) -> Self {
+		match sig {
+			SIGCHLD | SIGURG | SIGWINCH => Self::Ignore,
+			SIGCONT => Self::Continue,
+			_ if STOP_SIGNALS & sigmask(sig) != 0 => Self::Stop,
+			_ => Self::Terminate,
+		}
+	}
+}
+
+/// Name of `sig`, for the kernel's messages.
+pub fn signal_name(sig: usize) -> &'static str {
+	const NAMES: [&str; 31] = [
+		"SIGHUP",
+		"SIGINT",
+		"SIGQUIT",
+		"SIGILL",
+		"SIGTRAP",
+		"SIGABRT",
+		"SIGBUS",
+		"SIGFPE",
+		"SIGKILL",
+		"SIGUSR1",
+		"SIGSEGV",
+		"SIGUSR2",
+		"SIGPIPE",
+		"SIGALRM",
+		"SIGTERM",
+		"SIGSTKFLT",
+		"SIGCHLD",
+		"SIGCONT",
+		"SIGSTOP",
+		"SIGTSTP",
+		"SIGTTIN",
+		"SIGTTOU",
+		"SIGURG",
+		"SIGXCPU",
+		"SIGXFSZ",
+		"SIGVTALRM",
+		"SIGPROF",
+		"SIGWINCH",
+		"SIGIO",
+		"SIGPWR",
+		"SIGSYS",
+	];
+	NAMES.get(sig.wrapping_sub(1)).copied().unwrap_or("SIGRT")
+}
+
+/// Signal state of a task.
+pub struct SignalState {
+	/// raised and not handled yet
+	pub pending: SigSet,
+	/// kept pending until they are unblocked
+	pub blocked: SigSet,
+	/// action of signal `sig` at index `sig - 1`
+	actions:     [SigAction; NSIG],
+	/// stopped by a stop signal, until `SIGCONT`
+	frozen:      bool,
+	/// the signal that terminates the task
+	killed:      Option<usize>,
+	/// trap context and blocked set to go back to when the running handler
+	/// calls `rt_sigreturn`
+	saved:       Option<(TrapContext, SigSet)>,
+}
+
+impl SignalState {
+	pub fn new() -> Self {
+		Self {
+			pending: 0,
+			blocked: 0,
+			actions: [SigAction::default(); NSIG],
+			frozen:  false,
+			killed:  None,
+			saved:   None,
+		}
+	}
+
+	/// State of a forked child, which inherits everything but the pending
+	/// signals.
+	pub fn fork(&self) -> Self { Self { pending: 0, frozen: false, killed: None, ..*self } }
+
+	/// Reset for `execve`: the handlers are gone with the old program, so
+	/// caught signals go back to their default action.
+	pub fn exec(&mut self) {
+		for action in self.actions.iter_mut().filter(|action| action.handler != SIG_IGN) {
+			*action = SigAction::default();
+		}
+		self.saved = None;
+	}
+
+	pub fn action(&self, sig: usize) -> SigAction { self.actions[sig - 1] }
+
+	/// Change the action of `sig`, which must be catchable.
+	pub fn set_action(&mut self, sig: usize, action: SigAction) {
+		assert_eq!(UNCATCHABLE & sigmask(sig), 0);
+		self.actions[sig - 1] = SigAction { mask: action.mask & !UNCATCHABLE, ..action };
+	}
+
+	/// Change the blocked set, the uncatchable signals are never blocked.
+	pub fn set_blocked(&mut self, blocked: SigSet) { self.blocked = blocked & !UNCATCHABLE; }
+
+	/// Make `sig` pending.
+	pub fn raise(&mut self, sig: usize) {
+		if sig == SIGCONT {
+			// continuing happens when the signal is sent, even if it is caught
+			// or blocked
+			self.frozen = false;
+			self.pending &= !STOP_SIGNALS;
+		} else if STOP_SIGNALS & sigmask(sig) != 0 {
+			self.pending &= !sigmask(SIGCONT);
+		}
+		self.pending |= sigmask(sig);
+	}
+
+	/// Raise `sig` for a fault of the task. The faulting instruction runs
+	/// again after the signal, so unless a handler can take it right away
+	/// the task is terminated.
+	pub fn force(&mut self, sig: usize) {
+		let action = self.action(sig);
+		if action.handler == SIG_DFL
+			|| action.handler == SIG_IGN
+			|| self.blocked & sigmask(sig) != 0
+			|| self.saved.is_some()
+		{
+			self.killed = Some(sig);
+		} else {
+			self.raise(sig);
+		}
+	}
+
+	/// Pending signals that are not blocked.
+	fn deliverable(&self) -> SigSet { self.pending & !self.blocked }
+
+	/// Whether a signal should interrupt a blocking syscall, that is a
+	/// deliverable signal would not just be ignored.
+	pub fn interrupts(&self) -> bool {
+		(1..=NSIG).filter(|sig| self.deliverable() & sigmask(*sig) != 0).any(|sig| {
+			match self.action(sig).handler {
+				SIG_IGN => false,
+				SIG_DFL => DefaultAction::of(sig) != DefaultAction::Ignore,
+				_ => true,
+			}
+		})
+	}
+
+	pub fn frozen(&self) -> bool { self.frozen }
+
+	/// Whether a stopped task stays stopped, it is neither continued nor has
+	/// a `SIGKILL` pending.
+	pub fn stays_stopped(&self) -> bool { self.frozen && self.pending & sigmask(SIGKILL) == 0 }
+
+	pub fn killed(&self) -> Option<usize> { self.killed }
+
+	/// Handle the deliverable signals before returning to user mode through
+	/// `trap_cx`.
+	///
+	/// Default and ignored actions are applied right away, a handler is set up
+	/// to run for one signal at a time, the others stay pending until it
+	/// returns.
+	pub fn handle_pending(&mut self, trap_cx: &mut TrapContext) {
+		for sig in 1..=NSIG {
+			let mask = sigmask(sig);
+			if self.deliverable() & mask == 0 {
+				continue;
+			}
+			let action = self.action(sig);
+			if action.handler == SIG_DFL || UNCATCHABLE & mask != 0 {
+				self.pending &= !mask;
+				match DefaultAction::of(sig) {
+					DefaultAction::Terminate => {
+						self.killed = Some(sig);
+						return;
+					}
+					DefaultAction::Stop => self.frozen = true,
+					DefaultAction::Ignore | DefaultAction::Continue => {}
+				}
+			} else if action.handler == SIG_IGN {
+				self.pending &= !mask;
+			} else if self.saved.is_none() {
+				self.pending &= !mask;
+				self.saved = Some((*trap_cx, self.blocked));
+				self.blocked |= action.mask;
+				if action.flags & SA_NODEFER == 0 {
+					self.blocked |= mask;
+				}
+				trap_cx.x[1] = action.restorer;
+				trap_cx.x[10] = sig;
+				trap_cx.sepc = action.handler;
+			}
+		}
+	}
+
+	/// Leave the running handler, returns the trap context and blocked set
+	/// from before it, `None` if no handler is running.
+	pub fn take_saved(&mut self) -> Option<(TrapContext, SigSet)> { self.saved.take() }
+}
@@ -0,0 +1,69 @@
+//! Exit records of the last tasks, printed as a summary at shutdown.
+
+use alloc::{collections::VecDeque, string::String};
+
+use lazy_static::lazy_static;
+
+use crate::{config::MAX_EXIT_RECORDS, sync::UPSafeCell, task::{signal::signal_name, task::ExitReason}};
+
+/// How a task ended.
+struct ExitRecord {
+	pid:    usize,
+	/// app the task was running when it ended
+	name:   String,
+	reason: ExitReason,
+}
+
+/// The last `MAX_EXIT_RECORDS` exit records, and counts of every task that
+/// ended.
+#[derive(Default)]
+struct ExitSummary {
+	records: VecDeque<ExitRecord>,
+	exited:  usize,
+	failed:  usize,
+}
+
+lazy_static! {
+	static ref EXIT_SUMMARY: UPSafeCell<ExitSummary> = unsafe { UPSafeCell::new(ExitSummary::default()) };
+}
+
+/// Record that the task `pid` running `name` ended for `reason`, dropping
+/// the oldest record once there are `MAX_EXIT_RECORDS` of them.
+pub fn record_exit(pid: usize, name: String, reason: ExitReason) {
+	let mut summary = EXIT_SUMMARY.exclusive_access();
+	summary.exited += 1;
+	if reason.failed() {
+		summary.failed += 1;
+	}
+	if summary.records.len() == MAX_EXIT_RECORDS {
+		summary.records.pop_front();
+	}
+	summary.records.push_back(ExitRecord { pid, name, reason });
+}
+
+/// Print a table of the last tasks that ended, in the order they ended,
+/// and how many ended and failed in total.
+///
+/// Returns `true` if any task failed, that is exited with a non-zero code or
+/// was killed.
+pub fn print_summary() -> bool {
+	let summary = EXIT_SUMMARY.exclusive_access();
+	if summary.exited > summary.records.len() {
+		println!("[kernel] ... {} earlier tasks omitted", summary.exited - summary.records.len());
+	}
+	println!("[kernel] {:>5}  {:<20} {:<8} {}", "pid", "app", "status", "code");
+	for record in summary.records.iter() {
+		let status = match record.reason {
+			ExitReason::Exited(0) => "ok",
+			ExitReason::Exited(_) => "failed",
+			ExitReason::Killed(_) => "killed",
+		};
+		print!("[kernel] {:>5}  {:<20} {:<8} {}", record.pid, record.name, status, record.reason.exit_code());
+		if let ExitReason::Killed(sig) = record.reason {
+			print!(" ({})", signal_name(sig));
+		}
+		println!("");
+	}
+	println!("[kernel] {} tasks ended, {} failed", summary.exited, summary.failed);
+	summary.failed > 0
+}
@@ -0,0 +1,308 @@
+//! Types related to task management.
+
+use alloc::{string::String, sync::{Arc, Weak}, vec, vec::Vec};
+use core::cell::RefMut;
+
+pub use config::task::TaskStatus;
+use config::{auxv::{AT_ENTRY, AT_NULL, AT_PAGESZ, AT_PID}, task::MAX_SYSCALL_NUM};
+
+use crate::{config::{DEFAULT_PRIORITY, PAGE_SIZE, TRAP_CONTEXT}, memory::{KERNEL_SPACE, MemorySet, PhysPageNum, VirtAddr}, sync::UPSafeCell, task::{context::TaskContext, manager::insert_into_pid2task, pid::{KernelStack, PidHandle, pid_alloc}, scheduler::SchedEntity, signal::SignalState, usage::CpuUsage}, trap::{context::TrapContext, trap_handler}};
+
+/// Task control block, shared by the processor, the ready queue and the
+/// parent of the task.
+pub struct TaskControlBlock {
+	/// process identifier, recycled once the task is reaped
+	pub pid:          PidHandle,
+	/// kernel stack used while handling the task's traps, placed by the pid
+	pub kernel_stack: KernelStack,
+	/// mutable part of the task
+	inner:            UPSafeCell<TaskControlBlockInner>,
+}
+
+/// Mutable part of a [`TaskControlBlock`].
+pub struct TaskControlBlockInner {
+	/// name of the app the task is running
+	pub name:           String,
+	/// physical page of the task's `TrapContext`
+	pub trap_cx_ppn:    PhysPageNum,
+	pub task_cx:        TaskContext,
+	pub task_status:    TaskStatus,
+	/// address space of the task
+	pub memory_set:     MemorySet,
+	/// the task that forked this one, `None` once it has exited
+	pub parent:         Option<Weak<TaskControlBlock>>,
+	/// tasks forked by this one and not reaped yet
+	pub children:       Vec<Arc<TaskControlBlock>>,
+	/// how the task exited, handed to the parent by `wait4`
+	pub exit_reason:    ExitReason,
+	pub signals:        SignalState,
+	/// share of the CPU under the stride and CFS policies, at least
+	/// `MIN_PRIORITY`
+	pub priority:       usize,
+	/// state of the task kept by the scheduling policy
+	pub sched:          SchedEntity,
+	/// how often the task made each syscall, by id
+	pub syscall_times:  Vec<u32>,
+	/// when the task was first scheduled, in `time` ticks
+	pub first_run:      Option<u64>,
+	/// CPU time charged to the task
+	pub usage:          CpuUsage,
+	/// CPU time charged to the reaped children and their reaped descendants
+	pub children_usage: CpuUsage,
+	/// start of the heap area
+	pub heap_bottom:    usize,
+	/// current program break, the end of the heap
+	pub program_brk:    usize,
+}
+
+impl TaskControlBlockInner {
+	/// The task's `TrapContext`, reached through the kernel's identity
+	/// mapping.
+	pub fn get_trap_cx(&self) -> &'static mut TrapContext { self.trap_cx_ppn.get_mut() }
+
+	/// Token of the task's address space.
+	pub fn get_user_token(&self) -> usize { self.memory_set.token() }
+
+	pub fn is_zombie(&self) -> bool { self.task_status == TaskStatus::Zombie }
+
+	/// Move the program break to `brk`, growing or shrinking the heap.
+	///
+	/// Returns the program break afterwards, which is unchanged if `brk` is
+	/// below the heap or the heap cannot grow that far.
+	pub fn change_program_brk(&mut self, brk: usize) -> usize {
+		// addresses outside of the Sv39 user range can never be mapped
+		if brk < self.heap_bottom || usize::from(VirtAddr::from(brk)) != brk {
+			return self.program_brk;
+		}
+		let result = if brk < self.program_brk {
+			self.memory_set.shrink_heap_to(brk.into())
+		} else {
+			self.memory_set.append_heap_to(brk.into())
+		};
+		if result {
+			self.program_brk = brk;
+		}
+		self.program_brk
+	}
+}
+
+impl TaskControlBlock {
+	pub fn inner_exclusive_access(&self) -> RefMut<'_, TaskControlBlockInner> { self.inner.exclusive_access() }
+
+	pub fn getpid(&self) -> usize { self.pid.0 }
+
+	/// Build a task without parent running the app `name` from `elf_data`,
+	/// with `name` as its only argument and no environment.
+	pub fn new(name: &str, elf_data: &[u8]) -> Self {
+		Self::from_elf(name, elf_data, &[name.into()], &[], None, SignalState::new())
+	}
+
+	/// Build a task running the app `name` from `elf_data` in a fresh address
+	/// space, with the arguments `args` and the environment `envs`.
+	fn from_elf(
+		name: &str,
+		elf_data: &[u8],
+		args: &[String],
+		envs: &[String],
+		parent: Option<Weak<Self>>,
+		signals: SignalState,
+	) -> Self {
+		let pid = pid_alloc();
+		let (mut memory_set, user_stack_top, entry_point) = MemorySet::from_elf(elf_data);
+		let user_sp = push_args(&mut memory_set, user_stack_top, entry_point, pid.0, args, envs);
+		let trap_cx_ppn = memory_set.translate(VirtAddr::from(TRAP_CONTEXT).into()).unwrap().ppn();
+		let kernel_stack = KernelStack::new(&pid);
+		let kernel_sp = kernel_stack.get_sp();
+		let task_control_block = Self {
+			pid,
+			kernel_stack,
+			inner: unsafe {
+				UPSafeCell::new(TaskControlBlockInner {
+					name: name.into(),
+					trap_cx_ppn,
+					task_cx: TaskContext::goto_trap_return(kernel_sp),
+					task_status: TaskStatus::Ready,
+					memory_set,
+					parent,
+					children: Vec::new(),
+					exit_reason: ExitReason::Exited(0),
+					signals,
+					priority: DEFAULT_PRIORITY,
+					sched: SchedEntity::default(),
+					syscall_times: vec![0; MAX_SYSCALL_NUM],
+					first_run: None,
+					usage: CpuUsage::default(),
+					children_usage: CpuUsage::default(),
+					heap_bottom: user_stack_top,
+					program_brk: user_stack_top,
+				})
+			},
+		};
+		*task_control_block.inner_exclusive_access().get_trap_cx() = TrapContext::app_init_context(
+			entry_point,
+			user_sp,
+			KERNEL_SPACE.exclusive_access().token(),
+			kernel_sp,
+			trap_handler as *const () as usize,
+		);
+		task_control_block
+	}
+
+	/// Replace the task's address space with a new one running the app
+	/// `name` from `elf_data`, with the arguments `args` and the environment
+	/// `envs`.
+	pub fn exec(&self, name: &str, elf_data: &[u8], args: &[String], envs: &[String]) {
+		let (mut memory_set, user_stack_top, entry_point) = MemorySet::from_elf(elf_data);
+		let user_sp = push_args(&mut memory_set, user_stack_top, entry_point, self.getpid(), args, envs);
+		let trap_cx_ppn = memory_set.translate(VirtAddr::from(TRAP_CONTEXT).into()).unwrap().ppn();
+		let mut inner = self.inner_exclusive_access();
+		inner.name = name.into();
+		// the old address space is released here
+		inner.memory_set = memory_set;
+		inner.trap_cx_ppn = trap_cx_ppn;
+		inner.heap_bottom = user_stack_top;
+		inner.program_brk = user_stack_top;
+		inner.signals.exec();
+		*inner.get_trap_cx() = TrapContext::app_init_context(
+			entry_point,
+			user_sp,
+			KERNEL_SPACE.exclusive_access().token(),
+			self.kernel_stack.get_sp(),
+			trap_handler as *const () as usize,
+		);
+	}
+
+	/// Start the app `name` from `elf_data` with the arguments `args` as a
+	/// child of this task, in a fresh address space rather than a copy of
+	/// this one.
+	///
+	/// The child inherits the priority, and the signal actions like `execve`
+	/// after `fork` would, its environment is empty. It can be found by its
+	/// pid until it exits.
+	pub fn spawn(self: &Arc<Self>, name: &str, elf_data: &[u8], args: &[String]) -> Arc<Self> {
+		let mut parent_inner = self.inner_exclusive_access();
+		let mut signals = parent_inner.signals.fork();
+		signals.exec();
+		let task_control_block =
+			Arc::new(Self::from_elf(name, elf_data, args, &[], Some(Arc::downgrade(self)), signals));
+		let mut inner = task_control_block.inner_exclusive_access();
+		inner.priority = parent_inner.priority;
+		inner.sched = parent_inner.sched.clone();
+		drop(inner);
+		parent_inner.children.push(task_control_block.clone());
+		drop(parent_inner);
+		insert_into_pid2task(task_control_block.getpid(), task_control_block.clone());
+		task_control_block
+	}
+
+	/// Duplicate this task as a child of it, the child shares the user pages
+	/// copy-on-write and returns 0 from the syscall that forked it.
+	///
+	/// The child can be found by its pid until it exits.
+	pub fn fork(self: &Arc<Self>) -> Arc<Self> {
+		let mut parent_inner = self.inner_exclusive_access();
+		let memory_set = MemorySet::from_existed_user(&mut parent_inner.memory_set);
+		let trap_cx_ppn = memory_set.translate(VirtAddr::from(TRAP_CONTEXT).into()).unwrap().ppn();
+		let pid = pid_alloc();
+		let kernel_stack = KernelStack::new(&pid);
+		let kernel_sp = kernel_stack.get_sp();
+		let task_control_block = Arc::new(Self {
+			pid,
+			kernel_stack,
+			inner: unsafe {
+				UPSafeCell::new(TaskControlBlockInner {
+					name: parent_inner.name.clone(),
+					trap_cx_ppn,
+					task_cx: TaskContext::goto_trap_return(kernel_sp),
+					task_status: TaskStatus::Ready,
+					memory_set,
+					parent: Some(Arc::downgrade(self)),
+					children: Vec::new(),
+					exit_reason: ExitReason::Exited(0),
+					signals: parent_inner.signals.fork(),
+					priority: parent_inner.priority,
+					sched: parent_inner.sched.clone(),
+					syscall_times: vec![0; MAX_SYSCALL_NUM],
+					first_run: None,
+					usage: CpuUsage::default(),
+					children_usage: CpuUsage::default(),
+					heap_bottom: parent_inner.heap_bottom,
+					program_brk: parent_inner.program_brk,
+				})
+			},
+		});
+		parent_inner.children.push(task_control_block.clone());
+		let trap_cx = task_control_block.inner_exclusive_access().get_trap_cx();
+		trap_cx.kernel_sp = kernel_sp;
+		trap_cx.x[10] = 0;
+		insert_into_pid2task(task_control_block.getpid(), task_control_block.clone());
+		task_control_block
+	}
+}
+
+/// Lay out the initial user stack below `user_stack_top` the way the RISC-V
+/// Linux ABI does: `argc`, the `argv` and `envp` pointer arrays each ended
+/// by a null pointer and the auxiliary vector from the stack pointer up,
+/// with the strings above them. The auxiliary vector also carries `pid`, so
+/// that the app knows it without a syscall.
+///
+/// Returns the stack pointer, 16-byte aligned and pointing at `argc`. The
+/// caller checks that everything fits in [`ARG_MAX`].
+///
+/// [`ARG_MAX`]: crate::config::ARG_MAX
+fn push_args(
+	memory_set: &mut MemorySet,
+	user_stack_top: usize,
+	entry_point: usize,
+	pid: usize,
+	args: &[String],
+	envs: &[String],
+) -> usize {
+	let mut sp = user_stack_top;
+	let mut push_str = |s: &String| {
+		sp -= s.len() + 1;
+		assert!(memory_set.copy_to_user(sp, s.as_bytes()) && memory_set.copy_to_user(sp + s.len(), &[0]));
+		sp
+	};
+	let envp: Vec<usize> = envs.iter().map(&mut push_str).collect();
+	let argv: Vec<usize> = args.iter().map(&mut push_str).collect();
+	let mut words = vec![args.len()];
+	words.extend(argv);
+	words.push(0);
+	words.extend(envp);
+	words.push(0);
+	words.extend([AT_PAGESZ, PAGE_SIZE, AT_ENTRY, entry_point, AT_PID, pid, AT_NULL, 0]);
+	sp = (sp - words.len() * size_of::<usize>()) & !0xf;
+	let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_ne_bytes()).collect();
+	assert!(memory_set.copy_to_user(sp, &bytes));
+	sp
+}
+
+/// Why a task exited.
+#[derive(Copy, Clone)]
+pub enum ExitReason {
+	/// the task called `exit` with the code
+	Exited(i32),
+	/// the task was terminated by the signal
+	Killed(usize),
+}
+
+impl ExitReason {
+	/// Exit code of the task, the negated signal number for a killed task.
+	pub fn exit_code(&self) -> i32 {
+		match self {
+			Self::Exited(exit_code) => *exit_code,
+			Self::Killed(sig) => -(*sig as i32),
+		}
+	}
+
+	/// Status stored by `wait4`, encoded the way Linux does.
+	pub fn wait_status(&self) -> i32 {
+		match self {
+			Self::Exited(exit_code) => (exit_code & 0xff) << 8,
+			Self::Killed(sig) => *sig as i32,
+		}
+	}
+
+	pub fn failed(&self) -> bool { self.exit_code() != 0 }
+}
@@ -0,0 +1,60 @@
+//! Timer queue of the `Sleeping` tasks, ordered by deadline.
+
+use alloc::{collections::{BinaryHeap, binary_heap::PeekMut}, sync::{Arc, Weak}};
+use core::cmp::Ordering;
+
+use lazy_static::lazy_static;
+
+use crate::{sbi::get_time, sync::UPSafeCell, task::{TaskControlBlock, wake_task}};
+
+/// A task to wake once `time` reaches `deadline`.
+struct Timer {
+	/// in `mtime` ticks
+	deadline: u64,
+	/// weak, so that a task which is gone does not wait for its deadline to be
+	/// freed
+	task:     Weak<TaskControlBlock>,
+}
+
+impl PartialEq for Timer {
+	fn eq(&self, other: &Self) -> bool { self.deadline == other.deadline }
+}
+
+impl Eq for Timer {}
+
+impl PartialOrd for Timer {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+/// Reversed, so that the heap pops the earliest deadline first.
+impl Ord for Timer {
+	fn cmp(&self, other: &Self) -> Ordering { other.deadline.cmp(&self.deadline) }
+}
+
+lazy_static! {
+	static ref TIMERS: UPSafeCell<BinaryHeap<Timer>> = unsafe { UPSafeCell::new(BinaryHeap::new()) };
+}
+
+/// Wake `task` once `time` reaches `deadline`.
+pub fn add_timer(deadline: u64, task: &Arc<TaskControlBlock>) {
+	TIMERS.exclusive_access().push(Timer { deadline, task: Arc::downgrade(task) });
+}
+
+/// Wake the tasks whose deadline has passed.
+///
+/// A task woken early, by a signal, keeps its timer, which may wake it early
+/// from a later sleep too, sleepers check their deadline once woken.
+pub fn wake_expired_timers() {
+	let now = get_time();
+	loop {
+		let mut timers = TIMERS.exclusive_access();
+		let Some(timer) = timers.peek_mut().filter(|timer| timer.deadline <= now) else {
+			break;
+		};
+		let task = PeekMut::pop(timer).task;
+		drop(timers);
+		if let Some(task) = task.upgrade() {
+			wake_task(&task);
+		}
+	}
+}
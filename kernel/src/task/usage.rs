@@ -0,0 +1,72 @@
+//! CPU time accounting of the tasks.
+
+use config::syscall::TimeVal;
+
+use crate::config::{MICRO_PER_SEC, MTIME_FREQUENCY_HZ, TICKS_PER_SEC};
+
+/// CPU time a task was charged, in `time` ticks, and how often it was
+/// switched out.
+///
+/// The time since the task entered its current mode is charged when it
+/// leaves the mode: at trap entry and exit, and when it is switched out.
+#[derive(Copy, Clone, Default)]
+pub struct CpuUsage {
+	pub user_time:            u64,
+	pub kernel_time:          u64,
+	/// switches because the task blocked or yielded
+	pub voluntary_switches:   u64,
+	/// switches because the task was preempted
+	pub involuntary_switches: u64,
+	/// when the task entered its current mode, while it is running
+	since:                    u64,
+}
+
+impl CpuUsage {
+	/// The task starts running at `now`, in the kernel.
+	pub fn switch_in(&mut self, now: u64) { self.since = now; }
+
+	/// The task stops running at `now`, `voluntary` tells whether it gave up
+	/// the CPU itself.
+	pub fn switch_out(&mut self, now: u64, voluntary: bool) {
+		self.charge_kernel(now);
+		if voluntary {
+			self.voluntary_switches += 1;
+		} else {
+			self.involuntary_switches += 1;
+		}
+	}
+
+	/// The task trapped into the kernel at `now`.
+	pub fn enter_kernel(&mut self, now: u64) {
+		self.user_time += now - self.since;
+		self.since = now;
+	}
+
+	/// The task returns to user mode at `now`.
+	pub fn enter_user(&mut self, now: u64) { self.charge_kernel(now); }
+
+	/// Charge the time in the kernel up to `now`.
+	pub fn charge_kernel(&mut self, now: u64) {
+		self.kernel_time += now - self.since;
+		self.since = now;
+	}
+
+	/// Add the times and switches of `other`, for a reaped child.
+	pub fn add(&mut self, other: &CpuUsage) {
+		self.user_time += other.user_time;
+		self.kernel_time += other.kernel_time;
+		self.voluntary_switches += other.voluntary_switches;
+		self.involuntary_switches += other.involuntary_switches;
+	}
+}
+
+/// Convert `time` ticks to clock ticks of `times`.
+pub fn to_clock_ticks(time: u64) -> i64 { (time * TICKS_PER_SEC / MTIME_FREQUENCY_HZ) as i64 }
+
+/// Convert `time` ticks to a `TimeVal`.
+pub fn to_time_val(time: u64) -> TimeVal {
+	TimeVal {
+		sec:  time / MTIME_FREQUENCY_HZ,
+		usec: time % MTIME_FREQUENCY_HZ * MICRO_PER_SEC / MTIME_FREQUENCY_HZ,
+	}
+}
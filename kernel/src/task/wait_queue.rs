@@ -0,0 +1,41 @@
+//! Queues of `Sleeping` tasks waiting for an event, woken by whoever causes
+//! it.
+
+use alloc::{collections::VecDeque, sync::{Arc, Weak}};
+use core::mem;
+
+use crate::{sync::UPSafeCell, task::{TaskControlBlock, block_current_and_run_next, current_task, wake_task}};
+
+/// Tasks waiting for the same event.
+pub struct WaitQueue {
+	/// weak, so that a task which is gone does not wait for the event to be
+	/// freed
+	tasks: UPSafeCell<VecDeque<Weak<TaskControlBlock>>>,
+}
+
+impl WaitQueue {
+	pub fn new() -> Self { Self { tasks: unsafe { UPSafeCell::new(VecDeque::new()) } } }
+
+	/// Put current task to sleep until the queue is woken, then run next task.
+	///
+	/// A task woken early, by a signal, stays in the queue, which may wake it
+	/// early from a later sleep too, sleepers check what they wait for once
+	/// woken.
+	pub fn wait(&self) {
+		let task = Arc::downgrade(&current_task().unwrap());
+		let mut tasks = self.tasks.exclusive_access();
+		if !tasks.iter().any(|waiting| waiting.ptr_eq(&task)) {
+			tasks.push_back(task);
+		}
+		drop(tasks);
+		block_current_and_run_next();
+	}
+
+	/// Wake every task waiting in the queue.
+	pub fn wake_all(&self) {
+		let tasks = mem::take(&mut *self.tasks.exclusive_access());
+		for task in tasks.iter().filter_map(Weak::upgrade) {
+			wake_task(&task);
+		}
+	}
+}
@@ -1,14 +1,24 @@
 use riscv::register::sstatus::{self, SPP, Sstatus};
 
 /// Resources need to be store when trap is triggered.
+///
+/// It lives in the `TRAP_CONTEXT` page of the app's address space, `trap.S`
+/// depends on the field order.
 #[repr(C)]
+#[derive(Clone, Copy)]
 pub struct TrapContext {
 	/// 32 general registers
-	pub x:       [usize; 32],
+	pub x:            [usize; 32],
 	/// sstatus CSR
-	pub sstatus: Sstatus,
+	pub sstatus:      Sstatus,
 	/// return address after trap
-	pub sepc:    usize,
+	pub sepc:         usize,
+	/// token of the kernel address space
+	pub kernel_satp:  usize,
+	/// top of the app's kernel stack
+	pub kernel_sp:    usize,
+	/// address of `trap_handler` in the kernel address space
+	pub trap_handler: usize,
 }
 
 impl TrapContext {
@@ -16,13 +26,22 @@ impl TrapContext {
 	pub fn set_sp(&mut self, sp: usize) { self.x[2] = sp; }
 
 	/// init app context
-	pub fn app_init_context(entry: usize, sp: usize) -> Self {
+	pub fn app_init_context(
+		entry: usize,
+		sp: usize,
+		kernel_satp: usize,
+		kernel_sp: usize,
+		trap_handler: usize,
+	) -> Self {
 		let mut sstatus = sstatus::read(); // CSR sstatus
 		sstatus.set_spp(SPP::User); //previous privilege mode: user mode
 		let mut cx = Self {
 			x: [0; 32],
 			sstatus,
 			sepc: entry, // entry point of app
+			kernel_satp,
+			kernel_sp,
+			trap_handler,
 		};
 		cx.set_sp(sp); // app's user stack pointer
 		cx // return initial Trap Context of app
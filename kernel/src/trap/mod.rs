@@ -1,61 +1,133 @@
-use core::arch::global_asm;
+use core::arch::{asm, global_asm};
 
+use config::signal::{SIGBUS, SIGILL, SIGSEGV, SIGTRAP};
 use riscv::{interrupt::{Trap, supervisor::{Exception, Interrupt}}, register::{scause, sie, stval, stvec::{self, Stvec, TrapMode}}};
 
-use crate::{error, sbi::set_next_trigger, syscall::syscall, task::{exit_current_and_run_next, suspend_current_and_run_next}, trap::context::TrapContext};
+use crate::{config::{TRAMPOLINE, TRAP_CONTEXT}, console, error, memory::{MapPerm, VirtAddr}, sbi::set_next_trigger, syscall::syscall, task::{current_enter_kernel, current_enter_user, current_trap_cx, current_user_token, force_current_signal, handle_current_page_fault, handle_current_signals, preempt_current_and_run_next, tick_current_task, wake_expired_timers}};
 
 pub mod context;
 
 global_asm!(include_str!("trap.S"));
 
-/// Init trap with set stvec to Direct mode
-pub fn init() {
-	unsafe extern "C" {
-		fn __alltraps();
-	}
+/// Init trap, the kernel does not expect traps until it returns to user mode
+pub fn init() { set_kernel_trap_entry(); }
 
-	unsafe { stvec::write(Stvec::new(__alltraps as *const () as usize, TrapMode::Direct)) }
+/// Traps taken in S mode are bugs, send them to `trap_from_kernel`
+fn set_kernel_trap_entry() {
+	unsafe { stvec::write(Stvec::new(trap_from_kernel as *const () as usize, TrapMode::Direct)) }
 }
 
+/// Traps taken in U mode go to `__alltraps` through the trampoline
+fn set_user_trap_entry() { unsafe { stvec::write(Stvec::new(TRAMPOLINE, TrapMode::Direct)) } }
+
 pub fn enable_timer_interrupt() {
 	unsafe {
 		sie::set_stimer();
 	}
 }
 
+/// Handle a trap from user mode, `__alltraps` jumps here in the kernel
+/// address space.
 #[unsafe(no_mangle)]
-pub fn trap_handler(cx: &mut TrapContext) -> &mut TrapContext {
+pub fn trap_handler() -> ! {
+	set_kernel_trap_entry();
+	current_enter_kernel();
+	let cx = current_trap_cx();
 	let scause = scause::read();
 	let stval = stval::read();
 
 	match scause.cause().try_into::<Interrupt, Exception>().expect("Wrong trap type") {
 		Trap::Exception(Exception::UserEnvCall) => {
 			cx.sepc += 4;
-			// a7 - syscall ID, a0~a2: args, a0: also record return value
-			cx.x[10] = syscall(cx.x[17], [cx.x[10], cx.x[11], cx.x[12]]) as usize;
+			// a7 - syscall ID, a0~a5: args, a0: also record return value
+			let result = syscall(cx.x[17], [cx.x[10], cx.x[11], cx.x[12], cx.x[13], cx.x[14], cx.x[15]]);
+			// the syscall may have switched tasks, look the context up again
+			current_trap_cx().x[10] = result as usize;
 		}
-		Trap::Exception(Exception::StoreFault) | Trap::Exception(Exception::StorePageFault) => {
+		Trap::Exception(
+			e @ (Exception::LoadPageFault | Exception::StorePageFault | Exception::InstructionPageFault),
+		) => {
+			let access = match e {
+				Exception::LoadPageFault => MapPerm::R,
+				Exception::StorePageFault => MapPerm::W,
+				_ => MapPerm::X,
+			};
+			// lazily allocated pages are backed on their first access, `stval`
+			// outside of the Sv39 address range is never mapped
+			let va = VirtAddr::from(stval);
+			if usize::from(va) != stval || !handle_current_page_fault(va, access) {
+				error!(
+					"{e:?} in application, bad addr = {:#x}, bad instruction = {:#x}, raising SIGSEGV.",
+					stval, cx.sepc
+				);
+				force_current_signal(SIGSEGV);
+			}
+		}
+		Trap::Exception(e @ (Exception::LoadFault | Exception::StoreFault | Exception::InstructionFault)) => {
 			error!(
-				"PageFault in application, bad addr = {:#x}, bad instruction = {:#x}, kernel killed it.",
+				"{e:?} in application, bad addr = {:#x}, bad instruction = {:#x}, raising SIGSEGV.",
 				stval, cx.sepc
 			);
-			exit_current_and_run_next();
+			force_current_signal(SIGSEGV);
 		}
 		Trap::Exception(Exception::IllegalInstruction) => {
-			error!("IllegalInstruction in application, kernel killed it.");
-			exit_current_and_run_next();
+			error!("IllegalInstruction in application, bad instruction = {:#x}, raising SIGILL.", cx.sepc);
+			force_current_signal(SIGILL);
+		}
+		Trap::Exception(Exception::Breakpoint) => {
+			error!("Breakpoint in application at {:#x}, raising SIGTRAP.", cx.sepc);
+			force_current_signal(SIGTRAP);
 		}
 		Trap::Exception(e) => {
-			error!("{e:?} in application, kernel killed it.");
-			exit_current_and_run_next();
+			error!("{e:?} in application, bad addr = {:#x}, raising SIGBUS.", stval);
+			force_current_signal(SIGBUS);
 		}
 		Trap::Interrupt(Interrupt::SupervisorTimer) => {
 			set_next_trigger();
-			suspend_current_and_run_next();
+			wake_expired_timers();
+			console::poll_input();
+			if tick_current_task() {
+				preempt_current_and_run_next();
+			}
 		}
 		_ => {
 			panic!("Unsupported trap {:#?}, stval = {:#x}!", scause.cause(), stval)
 		}
 	}
-	cx
+	handle_current_signals();
+	trap_return();
+}
+
+/// Return to user mode through `__restore` in the trampoline, with the
+/// current task's `TrapContext` and address space.
+pub fn trap_return() -> ! {
+	current_enter_user();
+	set_user_trap_entry();
+	let trap_cx_ptr = TRAP_CONTEXT;
+	let user_satp = current_user_token();
+	unsafe extern "C" {
+		fn __alltraps();
+		fn __restore();
+	}
+	let restore_va = __restore as *const () as usize - __alltraps as *const () as usize + TRAMPOLINE;
+	unsafe {
+		asm!(
+			// app code may have been written through the data cache
+			"fence.i",
+			"jr {restore_va}",
+			restore_va = in(reg) restore_va,
+			in("a0") trap_cx_ptr,
+			in("a1") user_satp,
+			options(noreturn)
+		);
+	}
+}
+
+/// Traps are not expected while the kernel is running.
+///
+/// Aligned to 4 bytes because it is written into `stvec` directly.
+#[unsafe(no_mangle)]
+#[rustc_align(4)]
+pub fn trap_from_kernel() -> ! {
+	panic!("a trap {:?} from kernel, stval = {:#x}!", scause::read().cause(), stval::read());
 }
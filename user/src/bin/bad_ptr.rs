@@ -0,0 +1,22 @@
+//! Test that syscalls reject bad user pointers with `EFAULT` instead of
+//! crashing the kernel.
+
+#![no_std]
+#![no_main]
+
+use config::{errno::{EFAULT, EINVAL}, syscall::KernelTimespec};
+use user::{info, syscall::sys_nanosleep};
+
+#[unsafe(no_mangle)]
+fn main() -> i32 {
+	let null = core::ptr::null_mut();
+	// kernel memory, unmapped memory and a non-canonical address
+	for addr in [0x8020_0000usize, 0x1000, usize::MAX - 7] {
+		assert_eq!(sys_nanosleep(addr as *const KernelTimespec, null), -EFAULT, "addr {:#x}", addr);
+	}
+	assert_eq!(sys_nanosleep(core::ptr::null(), null), -EINVAL);
+	assert_eq!(sys_nanosleep(&KernelTimespec::nsec(-1), null), -EINVAL);
+	assert_eq!(sys_nanosleep(&KernelTimespec::nsec(1000), null), 0);
+	info!("Test bad_ptr OK!");
+	0
+}
@@ -0,0 +1,29 @@
+//! Test copy-on-write fork, parent and child see their own writes only.
+
+#![no_std]
+#![no_main]
+
+use user::{info, syscall::{sys_fork, sys_yield}};
+
+static mut SHARED: [u64; 512] = [7; 512];
+
+#[unsafe(no_mangle)]
+fn main() -> i32 {
+	let data = unsafe { (&raw mut SHARED).as_mut().unwrap() };
+	let pid = sys_fork();
+	if pid == 0 {
+		data.fill(1);
+		sys_yield();
+		assert!(data.iter().all(|&x| x == 1), "child sees the parent's writes");
+		info!("cow_fork child OK!");
+	} else {
+		assert!(pid > 0, "fork failed");
+		sys_yield();
+		assert!(data.iter().all(|&x| x == 7), "parent sees the child's writes");
+		data.fill(2);
+		sys_yield();
+		assert!(data.iter().all(|&x| x == 2));
+		info!("Test cow_fork OK!");
+	}
+	0
+}
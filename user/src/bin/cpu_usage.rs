@@ -0,0 +1,42 @@
+//! Test CPU time accounting: spinning is charged as user time, sleeping
+//! counts voluntary switches, and a reaped child adds to the children's
+//! usage.
+
+#![no_std]
+#![no_main]
+
+use core::ptr;
+
+use config::{resource::{RUSAGE_CHILDREN, RUSAGE_SELF}, syscall::{KernelTimespec, Rusage, Tms}};
+use user::{info, syscall::{sys_fork, sys_getrusage, sys_gettimeofday, sys_nanosleep, sys_times, waitpid}};
+
+/// Spin in user mode for `ms` milliseconds.
+fn spin(ms: isize) {
+	let start = sys_gettimeofday();
+	while sys_gettimeofday() - start < ms {}
+}
+
+#[unsafe(no_mangle)]
+fn main() -> i32 {
+	spin(200);
+	sys_nanosleep(&KernelTimespec::nsec(50_000_000), ptr::null_mut());
+	let mut usage = Rusage::default();
+	assert_eq!(sys_getrusage(RUSAGE_SELF, &mut usage), 0);
+	let utime_ms = usage.ru_utime.sec * 1000 + usage.ru_utime.usec / 1000;
+	assert!(utime_ms > 0 && utime_ms <= 250, "utime {}ms", utime_ms);
+	assert!(usage.ru_nvcsw > 0);
+
+	let child = sys_fork();
+	if child == 0 {
+		spin(200);
+		return 0;
+	}
+	assert_eq!(waitpid(child), Ok((child as usize, 0)));
+	let mut tms = Tms::default();
+	assert!(sys_times(&mut tms) > 0);
+	assert!(tms.tms_cutime > 0, "cutime {}", tms.tms_cutime);
+	assert_eq!(sys_getrusage(RUSAGE_CHILDREN, &mut usage), 0);
+	assert!(usage.ru_utime.sec > 0 || usage.ru_utime.usec > 0);
+	info!("Test cpu_usage OK!");
+	0
+}
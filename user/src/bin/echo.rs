@@ -0,0 +1,24 @@
+//! Print the arguments, and the environment if `-e` comes first.
+
+#![no_std]
+#![no_main]
+
+use user::{env, print, println};
+
+#[unsafe(no_mangle)]
+fn main() -> i32 {
+	let mut args = env::args().skip(1).peekable();
+	if args.next_if_eq(&"-e").is_some() {
+		for (key, value) in env::vars() {
+			println!("{}={}", key, value);
+		}
+	}
+	for (i, arg) in args.enumerate() {
+		if i > 0 {
+			print!(" ");
+		}
+		print!("{}", arg);
+	}
+	println!("");
+	0
+}
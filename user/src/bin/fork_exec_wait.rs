@@ -0,0 +1,37 @@
+//! Test fork, execve, spawn and wait4: children exit with their own codes
+//! and are reaped by the parent.
+
+#![no_std]
+#![no_main]
+
+use config::errno::{ECHILD, ENOENT};
+use user::{info, syscall::{sys_execve, sys_exit, sys_fork, sys_getpid, sys_getppid, sys_gettid, sys_spawn, wait, waitpid}};
+
+#[unsafe(no_mangle)]
+fn main() -> i32 {
+	let parent_pid = sys_getpid();
+	let pid = sys_fork();
+	if pid == 0 {
+		assert_eq!(sys_getppid(), parent_pid);
+		assert_eq!(sys_gettid(), sys_getpid());
+		sys_exit(42);
+	}
+	assert!(pid > 0, "fork failed");
+	assert_eq!(waitpid(pid), Ok((pid as usize, 42)));
+
+	let pid = sys_fork();
+	if pid == 0 {
+		assert_eq!(sys_execve("no_such_app\0", &[], &[]), -ENOENT);
+		sys_execve("echo\0", &["echo\0", "-e\0", "fork_exec_wait\0"], &["FROM=fork_exec_wait\0"]);
+		sys_exit(1);
+	}
+	assert_eq!(wait(), Ok((pid as usize, 0)));
+
+	assert_eq!(sys_spawn("no_such_app\0", &[]), -ENOENT);
+	let pid = sys_spawn("echo\0", &["echo\0", "spawned\0"]);
+	assert!(pid > 0, "spawn failed");
+	assert_eq!(waitpid(pid), Ok((pid as usize, 0)));
+	assert_eq!(wait(), Err(-ECHILD));
+	info!("Test fork_exec_wait OK!");
+	0
+}
@@ -0,0 +1,27 @@
+//! The first process: start the shell and reap every orphan, exit with the
+//! shell's code once the shell and all orphans are gone.
+
+#![no_std]
+#![no_main]
+
+use user::{println, syscall::{sys_execve, sys_fork, wait}};
+
+#[unsafe(no_mangle)]
+fn main() -> i32 {
+	let shell_pid = sys_fork();
+	if shell_pid == 0 {
+		sys_execve("user_shell\0", &["user_shell\0"], &[]);
+		panic!("initproc failed to start user_shell!");
+	}
+	let mut shell_exit_code = 0;
+	// orphans are only adopted from running processes, no child means no
+	// more orphans
+	while let Ok((pid, exit_code)) = wait() {
+		if pid == shell_pid as usize {
+			shell_exit_code = exit_code;
+		} else {
+			println!("[initproc] Released a zombie process, pid={}, exit_code={}", pid, exit_code);
+		}
+	}
+	shell_exit_code
+}
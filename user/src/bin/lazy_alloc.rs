@@ -0,0 +1,27 @@
+//! Test demand paging, the big bss array and the stack only get frames when
+//! they are touched.
+
+#![no_std]
+#![no_main]
+
+use user::info;
+
+const PAGES: usize = 16;
+const PAGE_SIZE: usize = 4096;
+
+static mut BUF: [u8; PAGES * PAGE_SIZE] = [0; PAGES * PAGE_SIZE];
+
+#[unsafe(no_mangle)]
+fn main() -> i32 {
+	let buf = unsafe { (&raw mut BUF).as_mut().unwrap() };
+	for (i, page) in buf.chunks_mut(PAGE_SIZE).enumerate() {
+		page[i] = i as u8;
+	}
+	let sum: usize = buf.chunks(PAGE_SIZE).enumerate().map(|(i, page)| page[i] as usize).sum();
+	assert_eq!(sum, (0..PAGES).sum());
+
+	let stack = [1u8; PAGE_SIZE];
+	assert_eq!(stack.iter().map(|&b| b as usize).sum::<usize>(), PAGE_SIZE);
+	info!("Test lazy_alloc OK!");
+	0
+}
@@ -0,0 +1,43 @@
+//! Test anonymous `mmap`, `mprotect` and `munmap`.
+
+#![no_std]
+#![no_main]
+
+use config::{errno::{EEXIST, EINVAL, ENOMEM}, mman::*};
+use user::{info, syscall::{sys_mmap, sys_mprotect, sys_munmap}};
+
+const PAGE_SIZE: usize = 4096;
+const LEN: usize = 4 * PAGE_SIZE;
+
+#[unsafe(no_mangle)]
+fn main() -> i32 {
+	let addr = sys_mmap(0, LEN, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+	assert!(addr > 0, "mmap failed with {}", addr);
+	let addr = addr as usize;
+	let buf = unsafe { core::slice::from_raw_parts_mut(addr as *mut u8, LEN) };
+	buf.fill(0xa5);
+	assert!(buf.iter().all(|&b| b == 0xa5));
+	info!("mapped [{:#x}, {:#x})", addr, addr + LEN);
+
+	// the range is taken, and invalid arguments are rejected
+	let fixed = MAP_PRIVATE | MAP_ANONYMOUS | MAP_FIXED;
+	assert_eq!(sys_mmap(addr, PAGE_SIZE, PROT_READ, fixed, -1, 0), -EEXIST);
+	assert_eq!(sys_mmap(addr + 1, PAGE_SIZE, PROT_READ, fixed, -1, 0), -EINVAL);
+	assert_eq!(sys_mmap(0, 0, PROT_READ, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0), -EINVAL);
+	assert_eq!(sys_mmap(0, PAGE_SIZE, PROT_READ, MAP_SHARED | MAP_ANONYMOUS, -1, 0), -EINVAL);
+
+	// make the middle read-only, which splits the mapping
+	assert_eq!(sys_mprotect(addr + PAGE_SIZE, 2 * PAGE_SIZE, PROT_READ), 0);
+	assert_eq!(buf[PAGE_SIZE], 0xa5);
+	buf[0] = 1;
+	buf[LEN - 1] = 1;
+	assert_eq!(sys_mprotect(addr + 1, PAGE_SIZE, PROT_READ), -EINVAL);
+
+	assert_eq!(sys_munmap(addr, LEN), 0);
+	assert_eq!(sys_mprotect(addr, PAGE_SIZE, PROT_READ), -ENOMEM);
+	// the range is free again
+	assert_eq!(sys_mmap(addr, PAGE_SIZE, PROT_READ | PROT_WRITE, fixed, -1, 0), addr as isize);
+	assert_eq!(sys_munmap(addr, PAGE_SIZE), 0);
+	info!("Test mmap_test OK!");
+	0
+}
@@ -0,0 +1,25 @@
+//! Test growing and shrinking the heap with `sbrk`.
+
+#![no_std]
+#![no_main]
+
+use user::{info, syscall::sbrk};
+
+const PAGE_SIZE: usize = 4096;
+const PAGES: usize = 4;
+
+#[unsafe(no_mangle)]
+fn main() -> i32 {
+	let heap = sbrk((PAGES * PAGE_SIZE) as isize);
+	assert_ne!(heap, -1, "sbrk failed to grow the heap");
+	let buf = unsafe { core::slice::from_raw_parts_mut(heap as *mut u8, PAGES * PAGE_SIZE) };
+	buf.fill(0x5a);
+	assert!(buf.iter().all(|&b| b == 0x5a));
+	info!("grew heap [{:#x}, {:#x})", heap, sbrk(0));
+
+	assert_eq!(sbrk(-((PAGES * PAGE_SIZE) as isize)), heap + (PAGES * PAGE_SIZE) as isize);
+	assert_eq!(sbrk(0), heap);
+	assert_eq!(sbrk(-1), -1, "heap shrunk below its bottom");
+	info!("Test sbrk_heap OK!");
+	0
+}
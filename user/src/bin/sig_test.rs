@@ -0,0 +1,51 @@
+//! Test signals: a caught SIGUSR1, blocking, a caught SIGSEGV and killing a
+//! runaway child.
+
+#![no_std]
+#![no_main]
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use config::signal::{SIG_BLOCK, SIG_UNBLOCK, SIGKILL, SIGSEGV, SIGUSR1, SigSet, sigmask};
+use user::{info, syscall::{signal, sys_exit, sys_fork, sys_getpid, sys_kill, sys_rt_sigprocmask, sys_yield, waitpid}};
+
+static CAUGHT: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn on_usr1(sig: usize) { CAUGHT.store(sig, Ordering::Relaxed); }
+
+extern "C" fn on_segv(sig: usize) { sys_exit(sig as i32); }
+
+#[unsafe(no_mangle)]
+fn main() -> i32 {
+	let pid = sys_getpid();
+	assert_eq!(signal(SIGUSR1, on_usr1, 0), 0);
+	sys_kill(pid, SIGUSR1);
+	assert_eq!(CAUGHT.swap(0, Ordering::Relaxed), SIGUSR1);
+
+	// a blocked signal waits until it is unblocked
+	let set: SigSet = sigmask(SIGUSR1);
+	sys_rt_sigprocmask(SIG_BLOCK, &set, core::ptr::null_mut());
+	sys_kill(pid, SIGUSR1);
+	assert_eq!(CAUGHT.load(Ordering::Relaxed), 0);
+	sys_rt_sigprocmask(SIG_UNBLOCK, &set, core::ptr::null_mut());
+	assert_eq!(CAUGHT.load(Ordering::Relaxed), SIGUSR1);
+
+	let child = sys_fork();
+	if child == 0 {
+		signal(SIGSEGV, on_segv, 0);
+		unsafe { (0x10 as *mut u8).write_volatile(0) };
+		unreachable!();
+	}
+	assert_eq!(waitpid(child), Ok((child as usize, SIGSEGV as i32)));
+
+	let child = sys_fork();
+	if child == 0 {
+		#[allow(clippy::empty_loop)]
+		loop {}
+	}
+	sys_yield();
+	assert_eq!(sys_kill(child, SIGKILL), 0);
+	assert_eq!(waitpid(child), Ok((child as usize, -(SIGKILL as i32))));
+	info!("Test sig_test OK!");
+	0
+}
@@ -0,0 +1,34 @@
+//! Test nanosleep: a sleep lasts at least as requested, and a signal
+//! interrupts it with the remaining time.
+
+#![no_std]
+#![no_main]
+
+use core::ptr;
+
+use config::{errno::EINTR, signal::SIGUSR1, syscall::KernelTimespec};
+use user::{info, syscall::{signal, sys_fork, sys_gettimeofday, sys_kill, sys_nanosleep, waitpid}};
+
+extern "C" fn on_usr1(_sig: usize) {}
+
+#[unsafe(no_mangle)]
+fn main() -> i32 {
+	let start = sys_gettimeofday();
+	assert_eq!(sys_nanosleep(&KernelTimespec::nsec(200_000_000), ptr::null_mut()), 0);
+	let elapsed = sys_gettimeofday() - start;
+	assert!(elapsed >= 200, "slept {}ms", elapsed);
+
+	let child = sys_fork();
+	if child == 0 {
+		signal(SIGUSR1, on_usr1, 0);
+		let mut rem = KernelTimespec::sec(0);
+		assert_eq!(sys_nanosleep(&KernelTimespec::sec(5), &mut rem), -EINTR);
+		assert!(rem.tv_sec < 5 && (rem.tv_sec, rem.tv_nsec) != (0, 0));
+		return 0;
+	}
+	sys_nanosleep(&KernelTimespec::nsec(100_000_000), ptr::null_mut());
+	assert_eq!(sys_kill(child, SIGUSR1), 0);
+	assert_eq!(waitpid(child), Ok((child as usize, 0)));
+	info!("Test sleep_test OK!");
+	0
+}
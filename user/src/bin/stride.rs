@@ -0,0 +1,76 @@
+//! Run busy children with different priorities for the same time, their
+//! counts and CPU times should be about proportional to their priorities.
+//!
+//! Only the stride and cfs policies weigh tasks by priority, under the others
+//! the CPU times are only reported. xtask tells the policy the kernel is
+//! built with in `KERNEL_SCHED`.
+
+#![no_std]
+#![no_main]
+
+use config::{resource::{PRIO_PROCESS, RUSAGE_CHILDREN}, syscall::{Rusage, TimeVal}};
+use user::{info, syscall::{sys_exit, sys_fork, sys_getrusage, sys_gettimeofday, sys_setpriority, waitpid}};
+
+/// How long every child counts, in milliseconds.
+const DURATION_MS: isize = 1000;
+
+/// Priorities of the children, each twice the one before.
+const PRIORITIES: [isize; 4] = [5, 10, 20, 40];
+
+/// Whether the scheduling policy of the kernel weighs tasks by priority,
+/// stride is the default.
+fn weighs_priority() -> bool { matches!(option_env!("KERNEL_SCHED"), None | Some("stride" | "cfs")) }
+
+fn count() -> usize {
+	let start = sys_gettimeofday();
+	let mut count = 0usize;
+	// the timer wraps around every 65536 seconds, good enough for a test
+	while sys_gettimeofday().wrapping_sub(start) < DURATION_MS {
+		count += 1;
+	}
+	count
+}
+
+/// CPU time of the reaped children so far, in microseconds.
+fn children_cpu_us() -> u64 {
+	let mut usage = Rusage::default();
+	assert_eq!(sys_getrusage(RUSAGE_CHILDREN, &mut usage), 0);
+	let us = |time: TimeVal| time.sec * 1_000_000 + time.usec;
+	us(usage.ru_utime) + us(usage.ru_stime)
+}
+
+#[unsafe(no_mangle)]
+fn main() -> i32 {
+	let pids = PRIORITIES.map(|prio| {
+		let pid = sys_fork();
+		if pid == 0 {
+			assert_eq!(sys_setpriority(PRIO_PROCESS, 0, prio), 0);
+			let count = count();
+			info!("priority {:>2}: count {:>8}, count/priority {}", prio, count, count / prio as usize);
+			sys_exit(0);
+		}
+		pid
+	});
+	// reap the children one by one, each adds its CPU time to the children's
+	// usage
+	let mut cpu_us = [0; PRIORITIES.len()];
+	let mut reaped_us = 0;
+	for (pid, child_us) in pids.into_iter().zip(cpu_us.iter_mut()) {
+		assert_eq!(waitpid(pid), Ok((pid as usize, 0)));
+		let total_us = children_cpu_us();
+		*child_us = total_us - reaped_us;
+		reaped_us = total_us;
+	}
+	info!("CPU time of priorities {:?}: {:?}us", PRIORITIES, cpu_us);
+	if !weighs_priority() {
+		info!("Test stride OK, the scheduling policy ignores priorities!");
+		return 0;
+	}
+	// twice the priority should get twice the CPU time, allow for the ticks a
+	// child runs before its priority is set and the coarse accounting
+	for pair in cpu_us.windows(2) {
+		assert!(pair[1] * 2 > pair[0] * 3, "CPU times {:?}us", cpu_us);
+	}
+	info!("Test stride OK!");
+	0
+}
@@ -0,0 +1,29 @@
+//! Test task_info: the syscalls made so far are counted and the time since
+//! the task was first scheduled covers a sleep.
+
+#![no_std]
+#![no_main]
+
+use core::ptr;
+
+use config::{syscall::{GETPID, KernelTimespec, NANOSLEEP, TASK_INFO, YIELD}, task::{TaskInfo, TaskStatus}};
+use user::{info, syscall::{sys_getpid, sys_nanosleep, sys_task_info, sys_yield}};
+
+#[unsafe(no_mangle)]
+fn main() -> i32 {
+	for _ in 0..3 {
+		sys_getpid();
+	}
+	sys_yield();
+	sys_nanosleep(&KernelTimespec::nsec(100_000_000), ptr::null_mut());
+	let mut ti = TaskInfo::default();
+	assert_eq!(sys_task_info(&mut ti), 0);
+	assert_eq!(ti.status, TaskStatus::Running);
+	assert_eq!(ti.syscall_times[GETPID], 3);
+	assert_eq!(ti.syscall_times[YIELD], 1);
+	assert_eq!(ti.syscall_times[NANOSLEEP], 1);
+	assert_eq!(ti.syscall_times[TASK_INFO], 1);
+	assert!(ti.time >= 100, "time {}ms", ti.time);
+	info!("Test task_info OK!");
+	0
+}
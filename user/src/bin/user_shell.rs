@@ -0,0 +1,93 @@
+//! A minimal shell: read a command per line, an app name and its
+//! arguments, and run it, until `exit`.
+
+#![no_std]
+#![no_main]
+
+use config::errno::ENOENT;
+use user::{print, println, syscall::{sys_spawn, waitpid}, system::getchar};
+
+const LF: u8 = b'\n';
+const CR: u8 = b'\r';
+const DL: u8 = 0x7f;
+const BS: u8 = 0x08;
+
+/// Longest command line, the last byte is kept for the NUL.
+const LINE_MAX: usize = 128;
+/// Most arguments of a command, the app name included.
+const ARGS_MAX: usize = 16;
+
+#[unsafe(no_mangle)]
+fn main() -> i32 {
+	println!("Rust user shell");
+	let mut line = [0u8; LINE_MAX];
+	let mut len = 0;
+	print!(">> ");
+	loop {
+		match getchar() {
+			LF | CR => {
+				println!("");
+				if &line[..len] == b"exit" {
+					return 0;
+				}
+				if len > 0 {
+					run(&mut line, len);
+					len = 0;
+				}
+				print!(">> ");
+			}
+			BS | DL => {
+				if len > 0 {
+					// move back, blank the character out and move back again
+					print!("\x08 \x08");
+					len -= 1;
+				}
+			}
+			c if (b' '..DL).contains(&c) && len < LINE_MAX - 1 => {
+				print!("{}", c as char);
+				line[len] = c;
+				len += 1;
+			}
+			_ => {}
+		}
+	}
+}
+
+/// Run the command in the first `len` bytes of `line`, the app name followed
+/// by its arguments separated by spaces, and wait for it.
+fn run(line: &mut [u8; LINE_MAX], len: usize) {
+	// every argument keeps a NUL after it, in place of the space
+	line[len] = 0;
+	line[..len].iter_mut().filter(|c| **c == b' ').for_each(|c| *c = 0);
+	// only printable ASCII gets into the line
+	let line = core::str::from_utf8(&line[..=len]).unwrap();
+	let mut args = [""; ARGS_MAX];
+	let mut argc = 0;
+	let mut start = 0;
+	for (i, _) in line.bytes().enumerate().filter(|(_, c)| *c == 0) {
+		if i > start && argc < ARGS_MAX {
+			args[argc] = &line[start..=i];
+			argc += 1;
+		}
+		start = i + 1;
+	}
+	if argc == 0 {
+		return;
+	}
+	let pid = sys_spawn(args[0], &args[..argc]);
+	if pid == -ENOENT {
+		println!("{}: command not found", args[0].trim_end_matches('\0'));
+		return;
+	} else if pid < 0 {
+		println!("Shell: spawn failed with {}", pid);
+		return;
+	}
+	match waitpid(pid) {
+		Ok((pid, exit_code)) => {
+			println!("Shell: Process {} exited with code {}", pid, exit_code);
+		}
+		Err(errno) => {
+			println!("Shell: waitpid failed with {}", errno);
+		}
+	}
+}
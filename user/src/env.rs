@@ -0,0 +1,99 @@
+//! Arguments and environment of the app, as the kernel laid them out on the
+//! initial user stack, and its pid.
+
+use core::{ffi::{CStr, c_char}, sync::atomic::{AtomicPtr, AtomicUsize, Ordering}};
+
+use config::auxv::{AT_NULL, AT_PID};
+
+use crate::syscall::sys_getpid;
+
+static ARGC: AtomicUsize = AtomicUsize::new(0);
+static ARGV: AtomicPtr<*const c_char> = AtomicPtr::new(core::ptr::null_mut());
+static ENVP: AtomicPtr<*const c_char> = AtomicPtr::new(core::ptr::null_mut());
+static PID: AtomicUsize = AtomicUsize::new(0);
+
+/// Record `argc`, `argv` and `envp` from the initial stack pointer `sp`, and
+/// the pid of the app from the auxiliary vector.
+///
+/// # Safety
+///
+/// `sp` must be the stack pointer the app started with.
+pub(crate) unsafe fn init(sp: *const usize) {
+	unsafe {
+		let argc = *sp;
+		let argv = sp.add(1) as *mut *const c_char;
+		ARGC.store(argc, Ordering::Relaxed);
+		ARGV.store(argv, Ordering::Relaxed);
+		// `argv` ends with a null pointer, `envp` comes right after it
+		let envp = argv.add(argc + 1);
+		ENVP.store(envp, Ordering::Relaxed);
+		// and the auxiliary vector right after the null pointer ending `envp`
+		let mut auxv = envp;
+		while !(*auxv).is_null() {
+			auxv = auxv.add(1);
+		}
+		let mut auxv = auxv.add(1) as *const [usize; 2];
+		while (*auxv)[0] != AT_NULL {
+			if (*auxv)[0] == AT_PID {
+				PID.store((*auxv)[1], Ordering::Relaxed);
+			}
+			auxv = auxv.add(1);
+		}
+	}
+}
+
+/// Forget the pid, a forked child has a pid of its own, which it asks for
+/// once it needs it.
+pub(crate) fn forget_pid() { PID.store(0, Ordering::Relaxed); }
+
+/// Iterator over the strings of a null terminated pointer array.
+pub struct CStrArray {
+	next: *const *const c_char,
+}
+
+impl Iterator for CStrArray {
+	type Item = &'static str;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.next.is_null() {
+			return None;
+		}
+		let ptr = unsafe { *self.next };
+		if ptr.is_null() {
+			return None;
+		}
+		self.next = unsafe { self.next.add(1) };
+		// strings the kernel copied are not checked, replace invalid ones
+		Some(unsafe { CStr::from_ptr(ptr) }.to_str().unwrap_or("\u{fffd}"))
+	}
+}
+
+/// Number of arguments, the name of the app included.
+pub fn argc() -> usize { ARGC.load(Ordering::Relaxed) }
+
+/// Arguments of the app, starting with its name.
+pub fn args() -> CStrArray { CStrArray { next: ARGV.load(Ordering::Relaxed) } }
+
+/// Name of the app, its first argument.
+pub fn name() -> &'static str { args().next().unwrap_or("?") }
+
+/// Pid of the app, passed by the kernel at startup, or asked for on the
+/// first use after a fork.
+pub fn pid() -> usize {
+	match PID.load(Ordering::Relaxed) {
+		0 => {
+			let pid = sys_getpid() as usize;
+			PID.store(pid, Ordering::Relaxed);
+			pid
+		}
+		pid => pid,
+	}
+}
+
+/// Environment of the app as `(key, value)` pairs.
+pub fn vars() -> impl Iterator<Item = (&'static str, &'static str)> {
+	CStrArray { next: ENVP.load(Ordering::Relaxed) }.map(|var| var.split_once('=').unwrap_or((var, "")))
+}
+
+/// Value of the environment variable `key`.
+pub fn var(key: &str) -> Option<&'static str> { vars().find(|(k, _)| *k == key).map(|(_, v)| v) }
@@ -3,14 +3,20 @@
 
 use crate::syscall::sys_exit;
 
+pub mod env;
 mod log;
 mod stack_trace;
 pub mod syscall;
 pub mod system;
 
+/// Entry of the app, `sp` points at `argc` followed by `argv`, `envp` and the
+/// auxiliary vector, hand it to [`start`] before anything touches the stack.
+#[unsafe(naked)]
 #[unsafe(no_mangle)]
 #[unsafe(link_section = ".text.entry")]
-pub extern "C" fn _start() -> ! {
+pub extern "C" fn _start() -> ! { core::arch::naked_asm!("mv a0, sp", "tail {start}", start = sym start) }
+
+extern "C" fn start(sp: *const usize) -> ! {
 	unsafe extern "C" {
 		safe fn stext(); // begin addr of text segment
 		safe fn etext(); // end addr of text segment
@@ -23,6 +29,7 @@ pub extern "C" fn _start() -> ! {
 	}
 	(sbss as *const () as usize..ebss as *const () as usize)
 		.for_each(|a| unsafe { (a as *mut u8).write_volatile(0) });
+	unsafe { env::init(sp) };
 
 	trace!("user app loaded");
 	trace!("text [{:#x}, {:#x})", stext as *const () as usize, etext as *const () as usize);
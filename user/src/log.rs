@@ -1,8 +1,9 @@
 #[macro_export]
 macro_rules! error {
     ($($arg:tt)+) => {
-        $crate::println!("\x1b[31m[ERROR][{}] {}\x1b[0m",
-            env!("CARGO_PKG_NAME"),
+        $crate::println!("\x1b[31m[ERROR][{}:{}] {}\x1b[0m",
+            $crate::env::name(),
+            $crate::env::pid(),
             format_args!($($arg)+)
         );
     };
@@ -11,8 +12,9 @@ macro_rules! error {
 #[macro_export]
 macro_rules! warn {
     ($($arg:tt)+) => {
-        $crate::println!("\x1b[93m[WARN][{}] {}\x1b[0m",
-            env!("CARGO_PKG_NAME"),
+        $crate::println!("\x1b[93m[WARN][{}:{}] {}\x1b[0m",
+            $crate::env::name(),
+            $crate::env::pid(),
             format_args!($($arg)+)
         );
     };
@@ -21,8 +23,9 @@ macro_rules! warn {
 #[macro_export]
 macro_rules! info {
     ($($arg:tt)+) => {
-        $crate::println!("\x1b[34m[INFO][{}] {}\x1b[0m",
-            env!("CARGO_PKG_NAME"),
+        $crate::println!("\x1b[34m[INFO][{}:{}] {}\x1b[0m",
+            $crate::env::name(),
+            $crate::env::pid(),
             format_args!($($arg)+)
         );
     };
@@ -31,8 +34,9 @@ macro_rules! info {
 #[macro_export]
 macro_rules! debug {
     ($($arg:tt)+) => {
-        $crate::println!("\x1b[32m[DEBUG][{}] {}\x1b[0m",
-            env!("CARGO_PKG_NAME"),
+        $crate::println!("\x1b[32m[DEBUG][{}:{}] {}\x1b[0m",
+            $crate::env::name(),
+            $crate::env::pid(),
             format_args!($($arg)+)
         );
     };
@@ -41,8 +45,9 @@ macro_rules! debug {
 #[macro_export]
 macro_rules! trace {
     ($($arg:tt)+) => {
-        $crate::println!("\x1b[90m[TRACE][{}] {}\x1b[0m",
-            env!("CARGO_PKG_NAME"),
+        $crate::println!("\x1b[90m[TRACE][{}:{}] {}\x1b[0m",
+            $crate::env::name(),
+            $crate::env::pid(),
             format_args!($($arg)+)
         );
     };
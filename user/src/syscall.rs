@@ -2,7 +2,7 @@
 
 use core::arch::asm;
 
-use config::syscall::*;
+use config::{signal::*, syscall::*, task::TaskInfo};
 
 /// Use `ecall` to generate `Environment call from U-mode Exception`, trap into
 /// S-mode. Which also calls `ABI` or `syscall`.
@@ -32,6 +32,25 @@ fn syscall(id: usize, args: [usize; 3]) -> isize {
 	ret
 }
 
+/// Same as [`syscall()`], for syscalls taking up to six arguments in
+/// `a0~a5`.
+fn syscall6(id: usize, args: [usize; 6]) -> isize {
+	let mut ret: isize;
+	unsafe {
+		asm!(
+				"ecall",
+				inlateout("x10") args[0] => ret,
+				in("x11") args[1],
+				in("x12") args[2],
+				in("x13") args[3],
+				in("x14") args[4],
+				in("x15") args[5],
+				in("x17") id
+		);
+	}
+	ret
+}
+
 /// `Function` - Write buffer in memory into file
 /// `Arguments`:
 ///     - `fd` - Fd to write
@@ -43,6 +62,17 @@ pub fn sys_write(fd: usize, buffer: &[u8]) -> isize {
 	syscall(WRITE, [fd, buffer.as_ptr() as usize, buffer.len()])
 }
 
+/// `Function` - Read from a file
+/// `Arguments`:
+///     - `fd` - Fd to read, only `STDIN` for now
+///     - `buf` - Buffer to fill
+/// `Return`: Number of bytes read, blocks until there is at least one, or
+/// `-EBADF` for other fds
+/// `syscall ID`: 63
+pub fn sys_read(fd: usize, buffer: &mut [u8]) -> isize {
+	syscall(READ, [fd, buffer.as_mut_ptr() as usize, buffer.len()])
+}
+
 /// `Function` - Exit application and tell the batch os
 /// `Arguments`:
 ///     - `exit_code` - Application's exit code
@@ -64,3 +94,257 @@ pub fn sys_gettimeofday() -> isize {
 		_ => -1,
 	}
 }
+
+/// `Function` - Set the scheduling priority of a task
+/// `Arguments`:
+///     - `which` - `PRIO_PROCESS`
+///     - `who` - Pid of the task, `0` for the current task
+///     - `prio` - Priority, at least `2`, a larger one gets more of the CPU
+/// `Return`: `0`, or a negative errno
+/// `syscall ID`: 140
+pub fn sys_setpriority(which: usize, who: usize, prio: isize) -> isize {
+	syscall(SETPRIORITY, [which, who, prio as usize])
+}
+
+/// `Function` - Get the CPU times of the current task and its reaped children
+/// `Arguments`:
+///     - `tms` - Where to store the times, in clock ticks, may be null
+/// `Return`: Clock ticks since boot, or a negative errno
+/// `syscall ID`: 153
+pub fn sys_times(tms: *mut Tms) -> isize { syscall(TIMES, [tms as usize, 0, 0]) }
+
+/// `Function` - Get the resource usage of the current task or its reaped
+/// children
+/// `Arguments`:
+///     - `who` - `RUSAGE_SELF`, `RUSAGE_THREAD` or `RUSAGE_CHILDREN`
+///     - `usage` - Where to store the usage, only the CPU times and context
+///       switches are filled in
+/// `Return`: `0`, or a negative errno
+/// `syscall ID`: 165
+pub fn sys_getrusage(who: isize, usage: &mut Rusage) -> isize {
+	syscall(GETRUSAGE, [who as usize, usage as *mut _ as usize, 0])
+}
+
+/// `Function` - Get the status, syscall counts and running time of the
+/// current task
+/// `Arguments`:
+///     - `ti` - Where to store the information
+/// `Return`: `0`, or a negative errno
+/// `syscall ID`: 410
+pub fn sys_task_info(ti: &mut TaskInfo) -> isize { syscall(TASK_INFO, [ti as *mut _ as usize, 0, 0]) }
+
+/// `Function` - Get the pid of the current task
+/// `Return`: Pid of the current task
+/// `syscall ID`: 172
+pub fn sys_getpid() -> isize { syscall(GETPID, [0, 0, 0]) }
+
+/// `Function` - Get the pid of the parent of the current task
+/// `Return`: Pid of the parent, `0` for `initproc`
+/// `syscall ID`: 173
+pub fn sys_getppid() -> isize { syscall(GETPPID, [0, 0, 0]) }
+
+/// `Function` - Get the thread id of the current task
+/// `Return`: Thread id, the same as the pid as every task has one thread
+/// `syscall ID`: 178
+pub fn sys_gettid() -> isize { syscall(GETTID, [0, 0, 0]) }
+
+/// `Function` - Set the end of the heap, the program break
+/// `Arguments`:
+///     - `addr` - New program break, `0` to query the current one
+/// `Return`: The program break afterwards, unchanged on failure
+/// `syscall ID`: 214
+pub fn sys_brk(addr: usize) -> isize { syscall(BRK, [addr, 0, 0]) }
+
+/// `Function` - Grow or shrink the heap by `increment` bytes
+/// `Arguments`:
+///     - `increment` - Bytes to add to the program break, may be negative
+/// `Return`: The previous program break, or `-1` on failure
+pub fn sbrk(increment: isize) -> isize {
+	let old_brk = sys_brk(0);
+	if increment == 0 {
+		return old_brk;
+	}
+	let new_brk = old_brk.wrapping_add(increment);
+	if sys_brk(new_brk as usize) == new_brk { old_brk } else { -1 }
+}
+
+/// `Function` - Duplicate the current task
+/// `Return`: Pid of the child in the parent, `0` in the child
+/// `syscall ID`: 220
+pub fn sys_fork() -> isize {
+	let pid = syscall(FORK, [0, 0, 0]);
+	if pid == 0 {
+		crate::env::forget_pid();
+	}
+	pid
+}
+
+/// Most arguments or environment variables [`sys_execve`] and
+/// [`sys_spawn`] pass on.
+const MAX_ARGS: usize = 32;
+
+/// Pointer to `s` as the C string the kernel expects, `s` must end with a
+/// `\0` so that the kernel does not read past it.
+fn c_str(s: &str) -> *const u8 {
+	assert!(s.ends_with('\0'), "{s:?} does not end with a \\0");
+	s.as_ptr()
+}
+
+/// The null terminated pointer array of `strs` the kernel expects, `None` if
+/// there are more than [`MAX_ARGS`].
+fn c_str_array(strs: &[&str]) -> Option<[*const u8; MAX_ARGS + 1]> {
+	if strs.len() > MAX_ARGS {
+		return None;
+	}
+	let mut ptrs = [core::ptr::null(); MAX_ARGS + 1];
+	for (ptr, s) in ptrs.iter_mut().zip(strs) {
+		*ptr = c_str(s);
+	}
+	Some(ptrs)
+}
+
+/// `Function` - Replace the current program with another app
+/// `Arguments`:
+///     - `path` - Name of the app, must end with a `\0`
+///     - `argv` - Arguments, the name of the app first, each must end with a
+///       `\0`
+///     - `envp` - Environment as `KEY=VALUE`, each must end with a `\0`
+/// `Return`: Does not return on success, a negative errno otherwise
+/// `syscall ID`: 221
+pub fn sys_execve(path: &str, argv: &[&str], envp: &[&str]) -> isize {
+	let (Some(argv), Some(envp)) = (c_str_array(argv), c_str_array(envp)) else {
+		return -config::errno::E2BIG;
+	};
+	syscall(EXECVE, [c_str(path) as usize, argv.as_ptr() as usize, envp.as_ptr() as usize])
+}
+
+/// `Function` - Start another app as a child in a fresh address space
+/// `Arguments`:
+///     - `path` - Name of the app, must end with a `\0`
+///     - `argv` - Arguments, the name of the app first, each must end with a
+///       `\0`
+/// `Return`: Pid of the child, or a negative errno
+/// `syscall ID`: 400
+pub fn sys_spawn(path: &str, argv: &[&str]) -> isize {
+	let Some(argv) = c_str_array(argv) else {
+		return -config::errno::E2BIG;
+	};
+	syscall(SPAWN, [c_str(path) as usize, argv.as_ptr() as usize, 0])
+}
+
+/// `Function` - Wait for a child to exit
+/// `Arguments`:
+///     - `pid` - Pid of the child, `-1` for any child
+///     - `wstatus` - Where to store the exit status, may be null
+///     - `options` - `0`, or `WNOHANG` to return at once
+/// `Return`: Pid of the reaped child, `0` with `WNOHANG` if no child has
+/// exited yet, or a negative errno
+/// `syscall ID`: 260
+pub fn sys_wait4(pid: isize, wstatus: *mut i32, options: usize) -> isize {
+	syscall6(WAIT4, [pid as usize, wstatus as usize, options, 0, 0, 0])
+}
+
+/// `Function` - Wait for the child `pid` (`-1` for any child) to exit
+/// `Return`: Pid of the reaped child and its exit code, the negated signal
+/// number if a signal killed it, or a negative errno
+pub fn waitpid(pid: isize) -> Result<(usize, i32), isize> {
+	let mut wstatus = 0;
+	match sys_wait4(pid, &mut wstatus, 0) {
+		ret if ret < 0 => Err(ret),
+		ret if wstatus & 0x7f != 0 => Ok((ret as usize, -(wstatus & 0x7f))),
+		ret => Ok((ret as usize, (wstatus >> 8) & 0xff)),
+	}
+}
+
+/// `Function` - Wait for any child to exit
+/// `Return`: Pid of the reaped child and its exit code, or a negative errno
+pub fn wait() -> Result<(usize, i32), isize> { waitpid(-1) }
+
+/// `Function` - Map anonymous private memory
+/// `Arguments`:
+///     - `addr` - Page aligned start address, a hint unless `flags` has
+///       `MAP_FIXED`, `0` to let the kernel choose
+///     - `len` - Length in bytes, rounded up to pages
+///     - `prot` - `PROT_*` bits
+///     - `flags` - `MAP_PRIVATE | MAP_ANONYMOUS`, optionally `MAP_FIXED`
+///     - `fd` - Ignored for anonymous mappings, pass `-1`
+///     - `offset` - Must be `0`
+/// `Return`: Start address of the mapping, or a negative errno
+/// `syscall ID`: 222
+pub fn sys_mmap(addr: usize, len: usize, prot: usize, flags: usize, fd: isize, offset: usize) -> isize {
+	syscall6(MMAP, [addr, len, prot, flags, fd as usize, offset])
+}
+
+/// `Function` - Unmap memory
+/// `Arguments`:
+///     - `addr` - Page aligned start address
+///     - `len` - Length in bytes
+/// `Return`: `0`, or a negative errno
+/// `syscall ID`: 215
+pub fn sys_munmap(addr: usize, len: usize) -> isize { syscall(MUNMAP, [addr, len, 0]) }
+
+/// `Function` - Change the protection of mapped memory
+/// `Arguments`:
+///     - `addr` - Page aligned start address
+///     - `len` - Length in bytes
+///     - `prot` - New `PROT_*` bits
+/// `Return`: `0`, or a negative errno
+/// `syscall ID`: 226
+pub fn sys_mprotect(addr: usize, len: usize, prot: usize) -> isize { syscall(MPROTECT, [addr, len, prot]) }
+
+/// `Function` - Send a signal to a task
+/// `Arguments`:
+///     - `pid` - Pid of the task
+///     - `sig` - Signal number, `0` only checks that the task exists
+/// `Return`: `0`, or a negative errno
+/// `syscall ID`: 129
+pub fn sys_kill(pid: isize, sig: usize) -> isize { syscall(KILL, [pid as usize, sig, 0]) }
+
+/// `Function` - Examine and change the action of a signal
+/// `Arguments`:
+///     - `sig` - Signal number
+///     - `act` - New action, may be null
+///     - `oldact` - Where to store the old action, may be null
+/// `Return`: `0`, or a negative errno
+/// `syscall ID`: 134
+pub fn sys_rt_sigaction(sig: usize, act: *const SigAction, oldact: *mut SigAction) -> isize {
+	syscall6(RT_SIGACTION, [sig, act as usize, oldact as usize, size_of::<SigSet>(), 0, 0])
+}
+
+/// `Function` - Examine and change the blocked signals
+/// `Arguments`:
+///     - `how` - `SIG_BLOCK`, `SIG_UNBLOCK` or `SIG_SETMASK`
+///     - `set` - Signals to apply with `how`, may be null
+///     - `oldset` - Where to store the old blocked set, may be null
+/// `Return`: `0`, or a negative errno
+/// `syscall ID`: 135
+pub fn sys_rt_sigprocmask(how: usize, set: *const SigSet, oldset: *mut SigSet) -> isize {
+	syscall6(RT_SIGPROCMASK, [how, set as usize, oldset as usize, size_of::<SigSet>(), 0, 0])
+}
+
+/// `Function` - Return from a signal handler
+/// `Return`: Never returns when a handler is running, `-EINVAL` otherwise
+/// `syscall ID`: 139
+pub fn sys_rt_sigreturn() -> isize { syscall(RT_SIGRETURN, [0, 0, 0]) }
+
+/// Where signal handlers return to.
+extern "C" fn sigreturn_trampoline() -> ! {
+	sys_rt_sigreturn();
+	unreachable!("rt_sigreturn returned!");
+}
+
+/// `Function` - Catch a signal with `handler`
+/// `Arguments`:
+///     - `sig` - Signal number
+///     - `handler` - Called with the signal number
+///     - `mask` - Signals to block while `handler` runs, besides `sig`
+/// `Return`: `0`, or a negative errno
+pub fn signal(sig: usize, handler: extern "C" fn(usize), mask: SigSet) -> isize {
+	let action = SigAction {
+		handler: handler as *const () as usize,
+		flags: SA_RESTORER,
+		restorer: sigreturn_trampoline as *const () as usize,
+		mask,
+	};
+	sys_rt_sigaction(sig, &action, core::ptr::null_mut())
+}
@@ -1,8 +1,8 @@
 use core::{fmt::{Arguments, Write}, panic::PanicInfo};
 
-use config::fd::STDOUT;
+use config::fd::{STDIN, STDOUT};
 
-use crate::{error, stack_trace::print_stack_trace, syscall::sys_write};
+use crate::{error, stack_trace::print_stack_trace, syscall::{sys_read, sys_write}};
 
 #[macro_export]
 macro_rules! print {
@@ -29,6 +29,13 @@ impl Write for Stdout {
 
 pub fn print(args: Arguments) { Stdout.write_fmt(args).unwrap() }
 
+/// Read a byte from the console, waiting for it.
+pub fn getchar() -> u8 {
+	let mut c = [0u8; 1];
+	sys_read(STDIN, &mut c);
+	c[0]
+}
+
 /// We need to use `#[panic_handler]` to
 /// specify [`panic_handler`] as panic handler.
 #[panic_handler]
@@ -1,4 +1,4 @@
-use std::{env, fs::{self, File, OpenOptions, read_dir}, io::{BufRead, BufReader, Seek, SeekFrom, Write}, path::{Path, PathBuf}, process::Command};
+use std::{env, fs::{self, File, read_dir}, io::Write, path::{Path, PathBuf}, process::Command};
 
 use clap::{Parser, Subcommand};
 use sha2::{Digest, Sha256};
@@ -15,8 +15,15 @@ struct Cli {
 impl Cli {
 	fn release(&self) -> bool {
 		match self.command {
-			Commands::Build { release } => release,
-			Commands::Run { qemu_args: _, release } => release,
+			Commands::Build { release, .. } => release,
+			Commands::Run { release, .. } => release,
+		}
+	}
+
+	fn sched(&self) -> Option<String> {
+		match &self.command {
+			Commands::Build { sched, .. } => sched.clone(),
+			Commands::Run { sched, .. } => sched.clone(),
 		}
 	}
 }
@@ -26,12 +33,18 @@ enum Commands {
 	Build {
 		#[arg(long)]
 		release: bool,
+		/// Scheduling policy of the kernel: rr, stride, mlfq or cfs
+		#[arg(long)]
+		sched:   Option<String>,
 	},
 	Run {
 		#[arg(trailing_var_arg = true, allow_hyphen_values = true)]
 		qemu_args: Vec<String>,
 		#[arg(long)]
 		release:   bool,
+		/// Scheduling policy of the kernel: rr, stride, mlfq or cfs
+		#[arg(long)]
+		sched:     Option<String>,
 	},
 }
 
@@ -43,6 +56,7 @@ struct Xtask {
 	workspace_dir:       PathBuf,
 	kernel_need_rebuild: bool,
 	apps:                Vec<String>,
+	sched:               Option<String>,
 }
 
 fn hash_dir(dir: &Path) -> anyhow::Result<Vec<u8>> {
@@ -76,6 +90,7 @@ fn main() -> anyhow::Result<()> {
 		workspace_dir,
 		kernel_need_rebuild: false,
 		apps: vec![],
+		sched: cli.sched(),
 	};
 
 	match cli.command {
@@ -158,6 +173,10 @@ impl Xtask {
 		if self.mode.eq("release") {
 			command.arg("--release");
 		}
+		// the policies exclude each other, the default stride has to go
+		if let Some(sched) = &self.sched {
+			command.args(["--no-default-features", "--features", &format!("kernel/sched-{sched}")]);
+		}
 		let status =
 			command.args(["--target", "riscv64gc-unknown-none-elf"]).env("RUSTFLAGS", &rustflags).status()?;
 
@@ -212,57 +231,24 @@ impl Xtask {
 		apps.sort();
 		self.apps = apps;
 
-		let file = OpenOptions::new().read(true).write(true).open(&linker_script)?;
-		let mut reader = BufReader::new(&file);
-		let mut offset = 0;
-		let mut line = String::new();
-		let mut writer = &file;
-		let base_address: u64 = 0x80400000;
-		let step: u64 = 0x20000;
-
-		loop {
-			line.clear();
-			let bytes = reader.read_line(&mut line)?;
-			if bytes == 0 {
-				break;
-			}
-			if line.trim_start().starts_with("BASE_ADDRESS") {
-				break;
-			}
-			offset += bytes as u64;
+		// Every app has its own address space, so they all share the base
+		// address in the linker script and can be built in one go.
+		println!("Building {}...", self.apps.join(", "));
+		let mut command = Command::new("cargo");
+		command.args(["build", "--package", "user", "--bins"]);
+		if self.mode.eq("release") {
+			command.arg("--release");
 		}
-
-		for (id, bin) in self.apps.iter().enumerate() {
-			println!("Building {bin}...");
-
-			writer.seek(SeekFrom::Start(offset))?;
-			let new_address = base_address + id as u64 * step;
-			let new_line = format!("BASE_ADDRESS = 0x{new_address:08x};\n");
-			if line.len().ne(&new_line.len()) {
-				anyhow::bail!("new line size must equals to the old one");
-			}
-			writer.write_all(new_line.as_bytes())?;
-			writer.flush()?;
-			let mut command = Command::new("cargo");
-			command.args(["build", "--bin", bin]);
-			if self.mode.eq("release") {
-				command.arg("--release");
-			}
-			let status =
-				command.args(["--target", "riscv64gc-unknown-none-elf"]).env("RUSTFLAGS", &rustflags).status()?;
-
-			if !status.success() {
-				anyhow::bail!("User build failed");
-			}
-
-			println!("Application {bin} start with address 0x{new_address:08x}");
+		// tests that depend on the scheduling policy read it at compile time
+		if let Some(sched) = &self.sched {
+			command.env("KERNEL_SCHED", sched);
 		}
+		let status =
+			command.args(["--target", "riscv64gc-unknown-none-elf"]).env("RUSTFLAGS", &rustflags).status()?;
 
-		// restore the base address
-		writer.seek(SeekFrom::Start(offset))?;
-		let new_line = format!("BASE_ADDRESS = 0x{base_address:08x};\n");
-		writer.write_all(new_line.as_bytes())?;
-		writer.flush()?;
+		if !status.success() {
+			anyhow::bail!("User build failed");
+		}
 
 		println!("✓ User build successful");
 		self.generate_user_app_data()
@@ -325,42 +311,42 @@ _num_app:
 		}
 		writeln!(app_link_file, r#"    .quad app_{}_end"#, self.apps.len() - 1)?;
 
+		// NUL terminated app names in the same order, to look apps up by name
+		writeln!(
+			app_link_file,
+			r#"
+    .global _app_names
+_app_names:"#
+		)?;
+		for app in self.apps.iter() {
+			writeln!(app_link_file, r#"    .string "{}""#, app)?;
+		}
+
 		for (idx, app) in self.apps.iter().enumerate() {
 			let app_binary = self.target_dir.join(app);
-			let aapp_bin_binary = app_binary.with_extension("bin");
 
 			if !app_binary.exists() {
 				anyhow::bail!("App binary not found at {}.", app_binary.display());
 			}
 
-			let status = Command::new("rust-objcopy")
-				.arg("--strip-all")
-				.arg(&app_binary)
-				.arg("-O")
-				.arg("binary")
-				.arg(&aapp_bin_binary)
-				.status()?;
-
-			if !status.success() {
-				anyhow::bail!("{} rust-objcopy failed with status: {:?}", app_binary.display(), status);
-			}
-
+			// The kernel parses the ELF in place, keep it 8-byte aligned.
 			writeln!(
 				app_link_file,
 				r#"
     .section .data
     .global app_{0}_start
     .global app_{0}_end
+    .align 3
 app_{0}_start:
-    .incbin "{1}.bin"
+    .incbin "{1}"
 app_{0}_end:"#,
 				idx,
-				self.target_dir.join(app).display()
+				app_binary.display()
 			)?;
 		}
 		println!("Generated {}", app_link_file_path.display());
-		// We do not realize elf yet, we need to clean the kernel, because it include
-		// the last user bins, it will not be recompiled
+		// Cargo does not track the ELFs pulled in by `.incbin`, we need to clean
+		// the kernel, otherwise it will not be rebuilt with the new apps
 		let status = Command::new("cargo").args(["clean", "--package", "kernel"]).status()?;
 
 		if !status.success() {